@@ -0,0 +1,45 @@
+email-is-empty = Email may not be empty.
+email-missing-at-sign = Email is missing @ symbol.
+email-multiple-at-signs = Email may only contain one @ symbol.
+email-invalid-local-part = Email has an invalid part before the @ symbol.
+email-invalid-domain = Email has an invalid domain.
+email-domain-not-allowed = Email domain is not allowed.
+email-too-short = Email must be at least { $limit } characters long.
+email-too-long = Email may not be longer than { $limit } characters long.
+
+name-is-empty = Name may not be empty.
+name-too-short = Name must be at least { $limit } characters long.
+name-too-long = Name may not be longer than { $limit } characters long.
+
+message-is-empty = Message may not be empty.
+message-too-short = Message must be at least { $limit } characters long.
+message-too-long = Message may not be longer than { $limit } characters long.
+
+field-required = This field is required.
+field-too-short = This field must be at least { $limit } characters long.
+field-too-long = This field may not be longer than { $limit } characters long.
+field-too-small = This field must be at least { $limit }.
+field-too-large = This field may not be more than { $limit }.
+field-invalid-email = This field must be an email address.
+field-invalid-phone = This field must be a phone number.
+field-invalid-number = This field must be a number.
+field-invalid-option = This field must be one of the listed options.
+field-invalid-boolean = This field must be yes or no.
+field-invalid-url = This field must be a web address.
+
+captcha-missing = Captcha may not be empty.
+captcha-rejected = Captcha verification failed.
+captcha-unavailable = Captcha could not be verified.
+
+spam-rejected = Message was rejected as spam.
+
+attachments-not-accepted = Attachments are not accepted.
+attachments-empty = Attachments may not be empty.
+attachments-too-many = No more than { $limit } attachments may be sent.
+attachments-too-large = Each attachment may not be larger than { $limit } bytes.
+attachments-total-too-large = Attachments may not be larger than { $limit } bytes in total.
+attachments-type-not-allowed = Attachment type is not allowed.
+
+body-malformed = Request body is malformed.
+body-too-large = Request body is too large.
+body-unsupported-type = Request body must be json, urlencoded or multipart form data.
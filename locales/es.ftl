@@ -0,0 +1,45 @@
+email-is-empty = El correo electrónico no puede estar vacío.
+email-missing-at-sign = Al correo electrónico le falta el símbolo @.
+email-multiple-at-signs = El correo electrónico solo puede contener un símbolo @.
+email-invalid-local-part = La parte antes del símbolo @ del correo electrónico no es válida.
+email-invalid-domain = El dominio del correo electrónico no es válido.
+email-domain-not-allowed = El dominio del correo electrónico no está permitido.
+email-too-short = El correo electrónico debe tener al menos { $limit } caracteres.
+email-too-long = El correo electrónico no puede tener más de { $limit } caracteres.
+
+name-is-empty = El nombre no puede estar vacío.
+name-too-short = El nombre debe tener al menos { $limit } caracteres.
+name-too-long = El nombre no puede tener más de { $limit } caracteres.
+
+message-is-empty = El mensaje no puede estar vacío.
+message-too-short = El mensaje debe tener al menos { $limit } caracteres.
+message-too-long = El mensaje no puede tener más de { $limit } caracteres.
+
+field-required = Este campo es obligatorio.
+field-too-short = Este campo debe tener al menos { $limit } caracteres.
+field-too-long = Este campo no puede tener más de { $limit } caracteres.
+field-too-small = Este campo debe ser al menos { $limit }.
+field-too-large = Este campo no puede ser mayor que { $limit }.
+field-invalid-email = Este campo debe ser una dirección de correo electrónico.
+field-invalid-phone = Este campo debe ser un número de teléfono.
+field-invalid-number = Este campo debe ser un número.
+field-invalid-option = Este campo debe ser una de las opciones indicadas.
+field-invalid-boolean = Este campo debe ser sí o no.
+field-invalid-url = Este campo debe ser una dirección web.
+
+captcha-missing = El captcha no puede estar vacío.
+captcha-rejected = La verificación del captcha falló.
+captcha-unavailable = No se pudo verificar el captcha.
+
+spam-rejected = El mensaje fue rechazado como spam.
+
+attachments-not-accepted = No se aceptan archivos adjuntos.
+attachments-empty = Los archivos adjuntos no pueden estar vacíos.
+attachments-too-many = No se pueden enviar más de { $limit } archivos adjuntos.
+attachments-too-large = Cada archivo adjunto no puede superar los { $limit } bytes.
+attachments-total-too-large = Los archivos adjuntos no pueden superar los { $limit } bytes en total.
+attachments-type-not-allowed = Este tipo de archivo adjunto no está permitido.
+
+body-malformed = El cuerpo de la solicitud está mal formado.
+body-too-large = El cuerpo de la solicitud es demasiado grande.
+body-unsupported-type = El cuerpo de la solicitud debe ser json, urlencoded o multipart form data.
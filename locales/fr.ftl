@@ -0,0 +1,45 @@
+email-is-empty = L'adresse e-mail ne peut pas être vide.
+email-missing-at-sign = Il manque le symbole @ dans l'adresse e-mail.
+email-multiple-at-signs = L'adresse e-mail ne peut contenir qu'un seul symbole @.
+email-invalid-local-part = La partie avant le symbole @ de l'adresse e-mail est invalide.
+email-invalid-domain = Le domaine de l'adresse e-mail est invalide.
+email-domain-not-allowed = Le domaine de l'adresse e-mail n'est pas autorisé.
+email-too-short = L'adresse e-mail doit contenir au moins { $limit } caractères.
+email-too-long = L'adresse e-mail ne peut pas dépasser { $limit } caractères.
+
+name-is-empty = Le nom ne peut pas être vide.
+name-too-short = Le nom doit contenir au moins { $limit } caractères.
+name-too-long = Le nom ne peut pas dépasser { $limit } caractères.
+
+message-is-empty = Le message ne peut pas être vide.
+message-too-short = Le message doit contenir au moins { $limit } caractères.
+message-too-long = Le message ne peut pas dépasser { $limit } caractères.
+
+field-required = Ce champ est obligatoire.
+field-too-short = Ce champ doit contenir au moins { $limit } caractères.
+field-too-long = Ce champ ne peut pas dépasser { $limit } caractères.
+field-too-small = Ce champ doit être au moins { $limit }.
+field-too-large = Ce champ ne peut pas dépasser { $limit }.
+field-invalid-email = Ce champ doit être une adresse e-mail.
+field-invalid-phone = Ce champ doit être un numéro de téléphone.
+field-invalid-number = Ce champ doit être un nombre.
+field-invalid-option = Ce champ doit être l'une des options proposées.
+field-invalid-boolean = Ce champ doit être oui ou non.
+field-invalid-url = Ce champ doit être une adresse web.
+
+captcha-missing = Le captcha ne peut pas être vide.
+captcha-rejected = La vérification du captcha a échoué.
+captcha-unavailable = Le captcha n'a pas pu être vérifié.
+
+spam-rejected = Le message a été rejeté comme spam.
+
+attachments-not-accepted = Les pièces jointes ne sont pas acceptées.
+attachments-empty = Les pièces jointes ne peuvent pas être vides.
+attachments-too-many = Vous ne pouvez pas envoyer plus de { $limit } pièces jointes.
+attachments-too-large = Chaque pièce jointe ne peut pas dépasser { $limit } octets.
+attachments-total-too-large = Les pièces jointes ne peuvent pas dépasser { $limit } octets au total.
+attachments-type-not-allowed = Ce type de pièce jointe n'est pas autorisé.
+
+body-malformed = Le corps de la requête est mal formé.
+body-too-large = Le corps de la requête est trop volumineux.
+body-unsupported-type = Le corps de la requête doit être au format json, urlencoded ou multipart form data.
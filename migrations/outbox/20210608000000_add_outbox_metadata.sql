@@ -0,0 +1,3 @@
+ALTER TABLE outbox ADD COLUMN client_ip TEXT;
+ALTER TABLE outbox ADD COLUMN user_agent TEXT;
+ALTER TABLE outbox ADD COLUMN referer TEXT;
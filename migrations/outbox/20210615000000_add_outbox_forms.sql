@@ -0,0 +1,2 @@
+ALTER TABLE outbox ADD COLUMN form_id TEXT;
+ALTER TABLE outbox ADD COLUMN fields TEXT NOT NULL DEFAULT '{}';
@@ -0,0 +1,9 @@
+CREATE TABLE outbox_attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    outbox_id INTEGER NOT NULL REFERENCES outbox (id),
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    content BLOB NOT NULL
+);
+
+CREATE INDEX outbox_attachments_outbox ON outbox_attachments (outbox_id);
@@ -0,0 +1 @@
+ALTER TABLE outbox ADD COLUMN submission_id INTEGER;
@@ -0,0 +1,5 @@
+CREATE TABLE outbox_deliveries (
+    outbox_id INTEGER NOT NULL REFERENCES outbox (id),
+    notifier TEXT NOT NULL,
+    PRIMARY KEY (outbox_id, notifier)
+);
@@ -0,0 +1,17 @@
+CREATE TABLE submissions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    created_at BIGINT NOT NULL,
+    form_id TEXT,
+    email TEXT NOT NULL,
+    name TEXT NOT NULL,
+    message TEXT NOT NULL,
+    fields TEXT NOT NULL DEFAULT '{}',
+    client_ip TEXT,
+    user_agent TEXT,
+    referer TEXT,
+    status TEXT NOT NULL,
+    spam_verdict TEXT NOT NULL,
+    spam_score BIGINT
+);
+
+CREATE INDEX submissions_created_at ON submissions (created_at);
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::settings::CaptchaSettings;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CaptchaError {
+    Missing,
+    Rejected,
+    Unavailable,
+}
+
+/// The response shared by hCaptcha, Cloudflare Turnstile and reCAPTCHA siteverify endpoints.
+#[derive(serde::Deserialize, Debug)]
+struct VerifyResponse {
+    success: bool,
+    #[serde(rename = "error-codes", default)]
+    error_codes: Vec<String>,
+}
+
+/// Verifies a submission's captcha response with the provider, disabled when not configured.
+pub struct Captcha {
+    client: reqwest::Client,
+    settings: Option<CaptchaSettings>,
+}
+
+impl Captcha {
+    pub fn new(settings: Option<CaptchaSettings>) -> Self {
+        let mut client = reqwest::Client::builder();
+        if let Some(settings) = &settings {
+            client = client.timeout(Duration::from_secs(settings.timeout_secs));
+        }
+
+        Self {
+            client: client.build().expect("Unable to build captcha client."),
+            settings,
+        }
+    }
+
+    /// Removes the response field from the submission's extra fields before verifying it.
+    #[tracing::instrument(name = "Verify captcha", skip(self, fields))]
+    pub async fn verify(
+        &self,
+        fields: &mut BTreeMap<String, serde_json::Value>,
+        remote_ip: Option<&str>,
+    ) -> Result<(), CaptchaError> {
+        let settings = match &self.settings {
+            Some(settings) => settings,
+            None => return Ok(()),
+        };
+
+        let response = match fields.remove(&settings.response_field) {
+            Some(serde_json::Value::String(response)) if !response.trim().is_empty() => response,
+            _ => return Err(CaptchaError::Missing),
+        };
+
+        let mut form = vec![
+            ("secret", settings.secret.as_str()),
+            ("response", response.as_str()),
+        ];
+        if let Some(remote_ip) = remote_ip {
+            form.push(("remoteip", remote_ip));
+        }
+
+        let unavailable = |error: reqwest::Error| {
+            tracing::error!("Unable to reach captcha provider: {}", error);
+            CaptchaError::Unavailable
+        };
+
+        let verified = self
+            .client
+            .post(&settings.verify_url)
+            .form(&form)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(unavailable)?
+            .json::<VerifyResponse>()
+            .await
+            .map_err(unavailable)?;
+
+        if verified.success {
+            Ok(())
+        } else {
+            tracing::info!("Captcha rejected: {:?}", verified.error_codes);
+            Err(CaptchaError::Rejected)
+        }
+    }
+}
@@ -0,0 +1,141 @@
+mod discord;
+mod matrix;
+mod mattermost;
+mod slack;
+
+use std::error::Error;
+use std::time::Duration;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::domain::contact::Contact;
+use super::domain::metadata::Metadata;
+
+pub use discord::Discord;
+pub use matrix::Matrix;
+pub use mattermost::Mattermost;
+pub use slack::Slack;
+
+const ELLIPSIS: char = '…';
+
+/// Cuts text to at most `max` characters on a grapheme boundary, marking the cut with an ellipsis.
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_owned();
+    }
+
+    let mut length = 0;
+    let mut truncated: String = text
+        .graphemes(true)
+        .take_while(|grapheme| {
+            length += grapheme.chars().count();
+            length < max
+        })
+        .collect();
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// Truncates before escaping, so escapes are never cut in half, searching for the longest cut
+/// that still fits once escaped.
+fn fit(text: &str, max: usize, escape: fn(&str) -> String) -> String {
+    let fits = |limit| {
+        let escaped = escape(&truncate(text, limit));
+        Some(escaped).filter(|escaped| escaped.chars().count() <= max)
+    };
+
+    if let Some(escaped) = fits(max) {
+        return escaped;
+    }
+
+    let (mut low, mut high) = (0, max);
+    while low + 1 < high {
+        let middle = (low + high) / 2;
+        if fits(middle).is_some() {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    fits(low).unwrap_or_default()
+}
+
+/// Escapes the markdown Discord and Mattermost render, so submissions can't format messages.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\*_~`|>#[]()".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// What every platform shows about a submission.
+struct Summary<'a> {
+    title: String,
+    fields: Vec<(String, String)>,
+    message: &'a str,
+    timestamp: String,
+}
+
+impl<'a> Summary<'a> {
+    fn new(contact: &'a Contact, metadata: &Metadata) -> Self {
+        let mut fields = vec![("Email".to_owned(), contact.email.as_ref().to_owned())];
+        if let Some(form) = &metadata.form_id {
+            fields.push(("Form".to_owned(), form.to_owned()));
+        }
+        fields.extend(
+            contact
+                .fields
+                .iter()
+                .map(|(name, value)| (name.to_owned(), value.to_owned())),
+        );
+        if let Some(score) = metadata.spam_score {
+            fields.push(("Spam score".to_owned(), score.to_string()));
+        }
+
+        Self {
+            title: format!("New contact from {}", contact.name.as_ref()),
+            fields,
+            message: contact.message.as_ref(),
+            timestamp: metadata.submitted_at.to_rfc3339(),
+        }
+    }
+}
+
+async fn send(request: reqwest::RequestBuilder, timeout: Duration) -> Result<(), Box<dyn Error>> {
+    request.timeout(timeout).send().await?.error_for_status()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncates_on_grapheme_boundaries() {
+        assert_eq!("Zoinks!", truncate("Zoinks!", 7));
+        assert_eq!("Zoin…", truncate("Zoinks!", 5));
+        assert_eq!("a…", truncate("ae\u{301}e\u{301}", 3));
+    }
+
+    #[test]
+    fn fits_escaped_text_within_the_limit() {
+        let fitted = fit("**********", 8, escape_markdown);
+
+        assert_eq!("\\*\\*\\*…", fitted);
+        assert!(fitted.chars().count() <= 8);
+    }
+
+    #[test]
+    fn escapes_markdown() {
+        assert_eq!(
+            "\\*Like\\*, \\[a link\\]\\(x\\) \\> \\`code\\`",
+            escape_markdown("*Like*, [a link](x) > `code`")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use serde_json::json;
+
+use super::{escape_markdown, fit, send, truncate, Summary};
+use crate::domain::contact::Contact;
+use crate::domain::metadata::Metadata;
+use crate::notifier::Notifier;
+use crate::settings::ChatWebhookSettings;
+
+const MAX_TITLE: usize = 256;
+const MAX_DESCRIPTION: usize = 4096;
+const MAX_FIELD_NAME: usize = 256;
+const MAX_FIELD_VALUE: usize = 1024;
+const MAX_FIELDS: usize = 25;
+/// Across the title, description and fields of an embed.
+const MAX_EMBED: usize = 6000;
+/// Kept for the description when fields have to be dropped to fit the embed.
+const MIN_DESCRIPTION: usize = 1024;
+
+fn length(fields: &[(String, String)]) -> usize {
+    fields
+        .iter()
+        .map(|(name, value)| name.chars().count() + value.chars().count())
+        .sum()
+}
+
+fn payload(summary: &Summary) -> serde_json::Value {
+    let title = truncate(&summary.title, MAX_TITLE);
+    let mut fields: Vec<(String, String)> = summary
+        .fields
+        .iter()
+        .take(MAX_FIELDS)
+        .map(|(name, value)| {
+            (
+                truncate(name, MAX_FIELD_NAME),
+                fit(value, MAX_FIELD_VALUE, escape_markdown),
+            )
+        })
+        .collect();
+
+    // Trailing fields go first, so the message always has some room left.
+    let reserved = MIN_DESCRIPTION.min(escape_markdown(summary.message).chars().count());
+    while title.chars().count() + length(&fields) + reserved > MAX_EMBED {
+        fields.pop();
+    }
+
+    let used = title.chars().count() + length(&fields);
+    let description = fit(
+        summary.message,
+        MAX_DESCRIPTION.min(MAX_EMBED.saturating_sub(used)),
+        escape_markdown,
+    );
+
+    json!({
+        // Submissions must never ping anyone, whatever they contain.
+        "allowed_mentions": { "parse": [] },
+        "embeds": [{
+            "title": title,
+            "description": description,
+            "timestamp": summary.timestamp,
+            "fields": fields
+                .into_iter()
+                .map(|(name, value)| json!({ "name": name, "value": value, "inline": true }))
+                .collect::<Vec<_>>(),
+        }]
+    })
+}
+
+/// Posts embeds to a channel webhook.
+pub struct Discord {
+    client: reqwest::Client,
+    settings: ChatWebhookSettings,
+}
+
+impl Discord {
+    pub fn new(settings: ChatWebhookSettings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+}
+
+impl Notifier for Discord {
+    fn name(&self) -> &str {
+        "discord"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        _: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        let request = self
+            .client
+            .post(&self.settings.webhook_url)
+            .json(&payload(&Summary::new(contact, metadata)));
+
+        send(request, Duration::from_secs(self.settings.timeout_secs)).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{markup_contact, metadata};
+
+    #[test]
+    fn formats_an_embed_with_escaped_markdown() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let payload = payload(&Summary::new(&contact, &metadata));
+        let embed = &payload["embeds"][0];
+
+        assert_eq!(json!([]), payload["allowed_mentions"]["parse"]);
+        assert_eq!("New contact from Shaggy & Scooby", embed["title"]);
+        assert_eq!(
+            "Zoinks! \\*Like\\*, there's a <ghost\\>!",
+            embed["description"]
+        );
+        assert_eq!("2021-06-01T00:00:00+00:00", embed["timestamp"]);
+        assert_eq!("Form", embed["fields"][1]["name"]);
+        assert_eq!("support", embed["fields"][1]["value"]);
+    }
+
+    #[test]
+    fn keeps_the_embed_within_its_total_limit() {
+        let contact = Contact::new(
+            "scooby@mystery.van",
+            "Shaggy",
+            &"Zoinks! ".repeat(1000),
+            &crate::domain::contact::ContactPolicy::UNLIMITED,
+        )
+        .unwrap();
+        let metadata = metadata();
+        let payload = payload(&Summary::new(&contact, &metadata));
+
+        let description = payload["embeds"][0]["description"].as_str().unwrap();
+        assert_eq!(MAX_DESCRIPTION, description.chars().count());
+        assert!(description.ends_with('…'));
+    }
+
+    #[test]
+    fn drops_fields_to_keep_the_embed_within_its_total_limit() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let mut summary = Summary::new(&contact, &metadata);
+        summary.fields = (0..25)
+            .map(|index| (format!("clue{:02}", index), "Jeepers ".repeat(128)))
+            .collect();
+        let payload = payload(&summary);
+        let embed = &payload["embeds"][0];
+
+        let fields = embed["fields"].as_array().unwrap();
+        let total = embed["title"].as_str().unwrap().chars().count()
+            + embed["description"].as_str().unwrap().chars().count()
+            + fields
+                .iter()
+                .map(|field| {
+                    field["name"].as_str().unwrap().chars().count()
+                        + field["value"].as_str().unwrap().chars().count()
+                })
+                .sum::<usize>();
+        assert!(total <= MAX_EMBED);
+        assert!(fields.len() < 25);
+        assert_eq!("clue00", fields[0]["name"]);
+        assert_eq!(
+            "Zoinks! \\*Like\\*, there's a <ghost\\>!",
+            embed["description"]
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use serde_json::json;
+
+use super::{send, truncate, Summary};
+use crate::domain::contact::Contact;
+use crate::domain::metadata::Metadata;
+use crate::notifier::{delivery_key, Notifier};
+use crate::settings::MatrixSettings;
+
+/// The homeserver rejects events over 65536 bytes, envelope included, so the content is kept
+/// under it with room to spare for the sender, room id, signatures and hashes it adds.
+const MAX_CONTENT: usize = 60_000;
+const MAX_FIELD: usize = 1024;
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// An `m.room.message` with a plain body, and html for clients that render it.
+fn content(title: &str, fields: &[(String, String)], message: &str) -> serde_json::Value {
+    let mut body = format!("{}\n", title);
+    let mut html = format!("<h4>{}</h4><ul>", escape_html(title));
+    for (name, value) in fields {
+        body.push_str(&format!("{}: {}\n", name, value));
+        html.push_str(&format!(
+            "<li><strong>{}</strong>: {}</li>",
+            escape_html(name),
+            escape_html(value)
+        ));
+    }
+    body.push_str(&format!("\n{}", message));
+    html.push_str(&format!("</ul><p>{}</p>", escape_html(message)));
+
+    json!({
+        "msgtype": "m.text",
+        "body": body,
+        "format": "org.matrix.custom.html",
+        "formatted_body": html,
+    })
+}
+
+/// Bytes the content takes once serialized, json escapes included.
+fn size(content: &serde_json::Value) -> usize {
+    content.to_string().len()
+}
+
+/// Drops trailing fields until the rest fit without a message, then searches for the longest
+/// cut of the message that still fits in `MAX_CONTENT` bytes.
+fn event(summary: &Summary) -> serde_json::Value {
+    let title = truncate(&summary.title, MAX_FIELD);
+    let mut fields: Vec<(String, String)> = summary
+        .fields
+        .iter()
+        .map(|(name, value)| (truncate(name, MAX_FIELD), truncate(value, MAX_FIELD)))
+        .collect();
+    while !fields.is_empty() && size(&content(&title, &fields, "")) > MAX_CONTENT {
+        fields.pop();
+    }
+
+    let fits = |limit| {
+        let event = content(&title, &fields, &truncate(summary.message, limit));
+        Some(event).filter(|event| size(event) <= MAX_CONTENT)
+    };
+
+    let length = summary.message.chars().count();
+    if let Some(event) = fits(length) {
+        return event;
+    }
+
+    let (mut low, mut high) = (0, length);
+    while low + 1 < high {
+        let middle = (low + high) / 2;
+        if fits(middle).is_some() {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    fits(low).unwrap_or_else(|| content(&title, &fields, ""))
+}
+
+/// Sends messages to a room through the client-server API.
+pub struct Matrix {
+    client: reqwest::Client,
+    settings: MatrixSettings,
+}
+
+impl Matrix {
+    pub fn new(settings: MatrixSettings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+
+    /// The transaction id stays the same across retries of an outbox entry, so the homeserver
+    /// deduplicates a retry of an event it already accepted.
+    fn url(&self, id: i64, metadata: &Metadata) -> Result<url::Url, Box<dyn Error>> {
+        let transaction = delivery_key(id, metadata);
+
+        let mut url = url::Url::parse(&self.settings.homeserver_url)?;
+        url.path_segments_mut()
+            .map_err(|_| "Homeserver url cannot be a base.")?
+            .pop_if_empty()
+            .extend(&[
+                "_matrix",
+                "client",
+                "v3",
+                "rooms",
+                &self.settings.room_id,
+                "send",
+                "m.room.message",
+                &transaction,
+            ]);
+
+        Ok(url)
+    }
+
+    async fn send(
+        &self,
+        id: i64,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        let request = self
+            .client
+            .put(self.url(id, metadata)?)
+            .bearer_auth(&self.settings.access_token)
+            .json(&event(&Summary::new(contact, metadata)));
+
+        send(request, Duration::from_secs(self.settings.timeout_secs)).await
+    }
+}
+
+impl Notifier for Matrix {
+    fn name(&self) -> &str {
+        "matrix"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        id: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        self.send(id, contact, metadata).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{markup_contact, metadata};
+
+    #[test]
+    fn formats_a_message_with_escaped_html() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let event = event(&Summary::new(&contact, &metadata));
+
+        assert_eq!("m.text", event["msgtype"]);
+        assert!(event["body"]
+            .as_str()
+            .unwrap()
+            .ends_with("\nZoinks! *Like*, there's a <ghost>!"));
+        assert_eq!(
+            "<h4>New contact from Shaggy &amp; Scooby</h4><ul>\
+             <li><strong>Email</strong>: scooby@mystery.van</li>\
+             <li><strong>Form</strong>: support</li>\
+             <li><strong>company</strong>: Mystery &lt;Inc&gt;</li>\
+             </ul><p>Zoinks! *Like*, there&#39;s a &lt;ghost&gt;!</p>",
+            event["formatted_body"]
+        );
+    }
+
+    #[test]
+    fn keeps_long_messages_under_the_byte_limit() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let message = "Jinkies! <👻> ".repeat(10_000);
+        let summary = Summary {
+            message: &message,
+            ..Summary::new(&contact, &metadata)
+        };
+
+        let event = event(&summary);
+
+        assert!(size(&event) <= MAX_CONTENT);
+        assert!(size(&event) > MAX_CONTENT - 100);
+        assert!(event["body"].as_str().unwrap().ends_with('…'));
+        assert!(event["formatted_body"].as_str().unwrap().ends_with("…</p>"));
+    }
+
+    #[test]
+    fn drops_fields_that_would_not_fit() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let mut summary = Summary::new(&contact, &metadata);
+        summary.fields = (0..100)
+            .map(|index| (format!("clue{:03}", index), "👻\"".repeat(1024)))
+            .collect();
+
+        let event = event(&summary);
+
+        assert!(size(&event) <= MAX_CONTENT);
+        let body = event["body"].as_str().unwrap();
+        assert!(body.contains("clue000: "));
+        assert!(!body.contains("clue099: "));
+    }
+
+    #[test]
+    fn reuses_the_transaction_id_when_retrying_an_entry() {
+        let matrix = Matrix::new(MatrixSettings {
+            homeserver_url: "https://matrix.mystery.van/".to_owned(),
+            access_token: "scooby-snack".to_owned(),
+            room_id: "!gang:mystery.van".to_owned(),
+            timeout_secs: 1,
+        });
+
+        let metadata = metadata();
+
+        assert_eq!(
+            "https://matrix.mystery.van/_matrix/client/v3/rooms/!gang:mystery.van/send/m.room.message/1622505600000.7",
+            matrix.url(7, &metadata).unwrap().as_str()
+        );
+        assert_eq!(
+            matrix.url(7, &metadata).unwrap(),
+            matrix.url(7, &metadata).unwrap()
+        );
+        assert_ne!(
+            matrix.url(7, &metadata).unwrap(),
+            matrix.url(8, &metadata).unwrap()
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use serde_json::json;
+
+use super::{escape_markdown, fit, send, truncate, Summary};
+use crate::domain::contact::Contact;
+use crate::domain::metadata::Metadata;
+use crate::notifier::Notifier;
+use crate::settings::MattermostSettings;
+
+/// Mattermost rejects posts longer than this many characters.
+const MAX_TEXT: usize = 16383;
+const MAX_TITLE: usize = 256;
+const MAX_FIELD: usize = 1024;
+
+fn payload(summary: &Summary, channel: Option<&str>) -> serde_json::Value {
+    let fields: Vec<_> = summary
+        .fields
+        .iter()
+        .map(|(name, value)| {
+            json!({
+                "short": true,
+                "title": truncate(name, MAX_TITLE),
+                "value": fit(value, MAX_FIELD, escape_markdown),
+            })
+        })
+        .collect();
+
+    let mut payload = json!({
+        "attachments": [{
+            "fallback": summary.title,
+            "title": truncate(&summary.title, MAX_TITLE),
+            "text": fit(summary.message, MAX_TEXT, escape_markdown),
+            "fields": fields,
+        }]
+    });
+
+    if let Some(channel) = channel {
+        payload["channel"] = json!(channel);
+    }
+
+    payload
+}
+
+/// Posts message attachments to an incoming webhook.
+pub struct Mattermost {
+    client: reqwest::Client,
+    settings: MattermostSettings,
+}
+
+impl Mattermost {
+    pub fn new(settings: MattermostSettings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+}
+
+impl Notifier for Mattermost {
+    fn name(&self) -> &str {
+        "mattermost"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        _: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        let summary = Summary::new(contact, metadata);
+        let request = self
+            .client
+            .post(&self.settings.webhook_url)
+            .json(&payload(&summary, self.settings.channel.as_deref()));
+
+        send(request, Duration::from_secs(self.settings.timeout_secs)).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{markup_contact, metadata};
+
+    #[test]
+    fn formats_an_attachment_with_escaped_markdown() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let payload = payload(&Summary::new(&contact, &metadata), Some("support"));
+        let attachment = &payload["attachments"][0];
+
+        assert_eq!("support", payload["channel"]);
+        assert_eq!("New contact from Shaggy & Scooby", attachment["title"]);
+        assert_eq!(
+            "Zoinks! \\*Like\\*, there's a <ghost\\>!",
+            attachment["text"]
+        );
+        assert_eq!("Email", attachment["fields"][0]["title"]);
+        assert_eq!("scooby@mystery.van", attachment["fields"][0]["value"]);
+    }
+
+    #[test]
+    fn leaves_the_channel_to_the_webhook_by_default() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let payload = payload(&Summary::new(&contact, &metadata), None);
+
+        assert!(payload.get("channel").is_none());
+    }
+}
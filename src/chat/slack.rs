@@ -0,0 +1,151 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use serde_json::json;
+
+use super::{fit, send, truncate, Summary};
+use crate::domain::contact::Contact;
+use crate::domain::metadata::Metadata;
+use crate::notifier::Notifier;
+use crate::settings::ChatWebhookSettings;
+
+const MAX_HEADER: usize = 150;
+const MAX_SECTION: usize = 3000;
+const MAX_FIELD: usize = 2000;
+/// Leaves most of a field's text to its value, however long a submitted field name is.
+const MAX_FIELD_NAME: usize = 256;
+/// Slack only renders the first ten fields of a section.
+const MAX_FIELDS: usize = 10;
+
+/// Slack's mrkdwn only needs the characters it uses for links and mentions escaped.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn payload(summary: &Summary) -> serde_json::Value {
+    let fields: Vec<_> = summary
+        .fields
+        .iter()
+        .take(MAX_FIELDS)
+        .map(|(name, value)| {
+            let name = format!("*{}*\n", fit(name, MAX_FIELD_NAME, escape));
+            let value = fit(
+                value,
+                MAX_FIELD.saturating_sub(name.chars().count()),
+                escape,
+            );
+            json!({ "type": "mrkdwn", "text": format!("{}{}", name, value) })
+        })
+        .collect();
+
+    json!({
+        "text": escape(&summary.title),
+        "blocks": [
+            {
+                "type": "header",
+                "text": { "type": "plain_text", "text": truncate(&summary.title, MAX_HEADER) }
+            },
+            { "type": "section", "fields": fields },
+            {
+                "type": "section",
+                "text": { "type": "mrkdwn", "text": fit(summary.message, MAX_SECTION, escape) }
+            }
+        ]
+    })
+}
+
+/// Posts Block Kit messages to an incoming webhook.
+pub struct Slack {
+    client: reqwest::Client,
+    settings: ChatWebhookSettings,
+}
+
+impl Slack {
+    pub fn new(settings: ChatWebhookSettings) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            settings,
+        }
+    }
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &str {
+        "slack"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        _: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        let request = self
+            .client
+            .post(&self.settings.webhook_url)
+            .json(&payload(&Summary::new(contact, metadata)));
+
+        send(request, Duration::from_secs(self.settings.timeout_secs)).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{markup_contact, metadata};
+
+    #[test]
+    fn formats_blocks_with_escaped_text() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let payload = payload(&Summary::new(&contact, &metadata));
+
+        assert_eq!(
+            "New contact from Shaggy & Scooby",
+            payload["blocks"][0]["text"]["text"]
+        );
+        assert_eq!(
+            "*Email*\nscooby@mystery.van",
+            payload["blocks"][1]["fields"][0]["text"]
+        );
+        assert_eq!(
+            "*company*\nMystery &lt;Inc&gt;",
+            payload["blocks"][1]["fields"][2]["text"]
+        );
+        assert_eq!(
+            "Zoinks! *Like*, there's a &lt;ghost&gt;!",
+            payload["blocks"][2]["text"]["text"]
+        );
+    }
+
+    #[test]
+    fn truncates_the_message_to_the_section_limit() {
+        let contact = Contact::new(
+            "scooby@mystery.van",
+            "Shaggy",
+            &"<".repeat(MAX_SECTION),
+            &crate::domain::contact::ContactPolicy::UNLIMITED,
+        )
+        .unwrap();
+        let metadata = metadata();
+        let payload = payload(&Summary::new(&contact, &metadata));
+
+        let text = payload["blocks"][2]["text"]["text"].as_str().unwrap();
+        assert!(text.chars().count() <= MAX_SECTION);
+        assert!(text.ends_with("&lt;…"));
+    }
+
+    #[test]
+    fn long_field_names_are_truncated() {
+        let (contact, metadata) = (markup_contact(), metadata());
+        let mut summary = Summary::new(&contact, &metadata);
+        summary.fields = vec![("<".repeat(5000), "Jinkies!".to_owned())];
+        let payload = payload(&summary);
+
+        let text = payload["blocks"][1]["fields"][0]["text"].as_str().unwrap();
+        assert!(text.chars().count() <= MAX_FIELD);
+        assert!(text.ends_with("…*\nJinkies!"));
+    }
+}
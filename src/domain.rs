@@ -1 +1,3 @@
+pub mod attachment;
 pub mod contact;
+pub mod metadata;
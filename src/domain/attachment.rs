@@ -0,0 +1,271 @@
+use std::fmt;
+
+use crate::settings::AttachmentSettings;
+
+#[derive(PartialEq, Eq, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+/// Logs the size rather than the content of uploads.
+impl fmt::Debug for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Attachment")
+            .field("filename", &self.filename)
+            .field("content_type", &self.content_type)
+            .field("size", &self.content.len())
+            .finish()
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    NotAccepted,
+    Empty,
+    TooMany(usize),
+    TooLarge(usize),
+    TotalTooLarge(usize),
+    TypeNotAllowed,
+}
+
+/// Keeps only the last path segment, so uploads can't name files outside where they are saved.
+fn sanitize(filename: &str) -> String {
+    let filename: String = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| !c.is_control())
+        .collect();
+
+    match filename.trim().trim_start_matches('.') {
+        "" => "attachment".to_owned(),
+        filename => filename.to_owned(),
+    }
+}
+
+/// Detects the type from magic bytes, treating anything that reads as text as `text/plain`.
+fn sniff(content: &[u8]) -> Option<&'static str> {
+    infer::get(content)
+        .map(|kind| kind.mime_type())
+        .or_else(|| {
+            let text = !content.contains(&0) && std::str::from_utf8(content).is_ok();
+            text.then_some("text/plain")
+        })
+}
+
+/// Accepts no uploads at all when not configured.
+pub struct AttachmentPolicy {
+    settings: Option<AttachmentSettings>,
+}
+
+impl AttachmentPolicy {
+    pub fn new(settings: Option<AttachmentSettings>) -> Self {
+        Self { settings }
+    }
+
+    /// How many files, and how many bytes of each, are worth reading to tell whether the limits
+    /// were broken, so oversized uploads never have to be held in memory.
+    pub fn read_limits(&self) -> (usize, usize) {
+        self.settings.as_ref().map_or((1, 0), |settings| {
+            (settings.max_files + 1, settings.max_file_bytes + 1)
+        })
+    }
+
+    /// Fails as soon as the file parts read so far add up to more than is allowed, so the rest of
+    /// a body needn't be read to reject it.
+    pub fn check_read(&self, total: usize) -> Result<(), Error> {
+        match &self.settings {
+            None if total > 0 => Err(Error::NotAccepted),
+            Some(settings) if total > settings.max_total_bytes => {
+                Err(Error::TotalTooLarge(settings.max_total_bytes))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn attachment(
+        settings: &AttachmentSettings,
+        filename: &str,
+        content: Vec<u8>,
+    ) -> Result<Attachment, Error> {
+        if content.is_empty() {
+            return Err(Error::Empty);
+        }
+
+        if content.len() > settings.max_file_bytes {
+            return Err(Error::TooLarge(settings.max_file_bytes));
+        }
+
+        let filename = sanitize(filename);
+        let extension = filename
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_lowercase())
+            .unwrap_or_default();
+        let content_type = sniff(&content).ok_or(Error::TypeNotAllowed)?;
+
+        let allowed = settings.allowed_types.iter().any(|allowed| {
+            allowed.mime == content_type
+                && allowed
+                    .extensions
+                    .iter()
+                    .any(|allowed| allowed.eq_ignore_ascii_case(&extension))
+        });
+
+        if allowed {
+            Ok(Attachment {
+                filename,
+                content_type: content_type.to_owned(),
+                content,
+            })
+        } else {
+            Err(Error::TypeNotAllowed)
+        }
+    }
+
+    pub fn validate(&self, uploads: Vec<(String, Vec<u8>)>) -> Result<Vec<Attachment>, Error> {
+        if uploads.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let settings = self.settings.as_ref().ok_or(Error::NotAccepted)?;
+
+        if uploads.len() > settings.max_files {
+            return Err(Error::TooMany(settings.max_files));
+        }
+
+        let total: usize = uploads.iter().map(|(_, content)| content.len()).sum();
+        if total > settings.max_total_bytes {
+            return Err(Error::TotalTooLarge(settings.max_total_bytes));
+        }
+
+        uploads
+            .into_iter()
+            .map(|(filename, content)| Self::attachment(settings, &filename, content))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::AllowedTypeSettings;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const PDF: &[u8] = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n";
+
+    fn policy() -> AttachmentPolicy {
+        AttachmentPolicy::new(Some(AttachmentSettings {
+            max_files: 2,
+            max_file_bytes: 32,
+            max_total_bytes: 48,
+            allowed_types: vec![
+                AllowedTypeSettings {
+                    mime: "image/png".to_owned(),
+                    extensions: vec!["png".to_owned()],
+                },
+                AllowedTypeSettings {
+                    mime: "text/plain".to_owned(),
+                    extensions: vec!["txt".to_owned()],
+                },
+            ],
+        }))
+    }
+
+    fn upload(filename: &str, content: &[u8]) -> (String, Vec<u8>) {
+        (filename.to_owned(), content.to_vec())
+    }
+
+    #[test]
+    fn accepts_allowed_types_by_content() {
+        let attachments = policy()
+            .validate(vec![
+                upload("clue.PNG", PNG),
+                upload("notes.txt", b"Jinkies!"),
+            ])
+            .unwrap();
+
+        assert_eq!("image/png", attachments[0].content_type);
+        assert_eq!("clue.PNG", attachments[0].filename);
+        assert_eq!("text/plain", attachments[1].content_type);
+    }
+
+    #[test]
+    fn rejects_content_that_does_not_match_the_allowlist() {
+        assert_eq!(
+            Err(Error::TypeNotAllowed),
+            policy().validate(vec![upload("clue.png", PDF)])
+        );
+        assert_eq!(
+            Err(Error::TypeNotAllowed),
+            policy().validate(vec![upload("clue.txt", PNG)])
+        );
+        assert_eq!(
+            Err(Error::TypeNotAllowed),
+            policy().validate(vec![upload("clue", PNG)])
+        );
+    }
+
+    #[test]
+    fn enforces_the_count_and_size_limits() {
+        assert_eq!(
+            Err(Error::TooMany(2)),
+            policy().validate(vec![
+                upload("a.txt", b"a"),
+                upload("b.txt", b"b"),
+                upload("c.txt", b"c"),
+            ])
+        );
+        assert_eq!(
+            Err(Error::TooLarge(32)),
+            policy().validate(vec![upload("a.txt", &[b'a'; 33])])
+        );
+        assert_eq!(
+            Err(Error::TotalTooLarge(48)),
+            policy().validate(vec![
+                upload("a.txt", &[b'a'; 30]),
+                upload("b.txt", &[b'b'; 30]),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_empty_uploads() {
+        assert_eq!(
+            Err(Error::Empty),
+            policy().validate(vec![upload("notes.txt", b"")])
+        );
+    }
+
+    #[test]
+    fn reading_stops_once_the_total_is_exceeded() {
+        assert_eq!(Ok(()), policy().check_read(48));
+        assert_eq!(Err(Error::TotalTooLarge(48)), policy().check_read(49));
+
+        let disabled = AttachmentPolicy::new(None);
+        assert_eq!(Ok(()), disabled.check_read(0));
+        assert_eq!(Err(Error::NotAccepted), disabled.check_read(1));
+    }
+
+    #[test]
+    fn disabled_policy_accepts_no_uploads() {
+        let policy = AttachmentPolicy::new(None);
+
+        assert_eq!(Ok(Vec::new()), policy.validate(Vec::new()));
+        assert_eq!(
+            Err(Error::NotAccepted),
+            policy.validate(vec![upload("a.txt", b"a")])
+        );
+    }
+
+    #[test]
+    fn sanitizes_filenames() {
+        assert_eq!("passwd", sanitize("../../etc/passwd"));
+        assert_eq!("clue.png", sanitize("C:\\Users\\fred\\clue.png"));
+        assert_eq!("htaccess", sanitize(".htaccess"));
+        assert_eq!("attachment", sanitize("../"));
+        assert_eq!("ab.txt", sanitize("a\u{0}b.txt"));
+    }
+}
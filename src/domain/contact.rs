@@ -1,12 +1,20 @@
 pub mod email;
+pub mod field;
+mod length;
 pub mod message;
 pub mod name;
 
+use std::collections::BTreeMap;
+
+use super::attachment::Attachment;
+
 use email::Email;
 use message::Message;
 use name::Name;
 
 pub use email::Error as EmailError;
+pub use field::Error as FieldError;
+pub use length::LengthPolicy;
 pub use message::Error as MessageError;
 pub use name::Error as NameError;
 
@@ -15,6 +23,8 @@ pub struct Contact {
     pub email: Email,
     pub name: Name,
     pub message: Message,
+    pub fields: BTreeMap<String, String>,
+    pub attachments: Vec<Attachment>,
 }
 
 #[derive(Debug, PartialEq, Eq)]
@@ -24,13 +34,50 @@ pub struct Error {
     pub message: Option<MessageError>,
 }
 
+/// Length limits for each field of a contact.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ContactPolicy {
+    pub email: LengthPolicy,
+    pub name: LengthPolicy,
+    pub message: LengthPolicy,
+}
+
+impl Default for ContactPolicy {
+    fn default() -> Self {
+        Self {
+            email: LengthPolicy { min: 1, max: 300 },
+            name: LengthPolicy { min: 1, max: 200 },
+            message: LengthPolicy { min: 1, max: 2000 },
+        }
+    }
+}
+
+impl ContactPolicy {
+    pub const UNLIMITED: Self = Self {
+        email: LengthPolicy::UNLIMITED,
+        name: LengthPolicy::UNLIMITED,
+        message: LengthPolicy::UNLIMITED,
+    };
+}
+
 impl Contact {
-    pub fn new(email: &str, name: &str, message: &str) -> Result<Self, Error> {
-        match (Email::new(email), Name::new(name), Message::new(message)) {
+    pub fn new(
+        email: &str,
+        name: &str,
+        message: &str,
+        policy: &ContactPolicy,
+    ) -> Result<Self, Error> {
+        match (
+            Email::new(email, &policy.email),
+            Name::new(name, &policy.name),
+            Message::new(message, &policy.message),
+        ) {
             (Ok(email), Ok(name), Ok(message)) => Ok(Self {
                 email,
                 name,
                 message,
+                fields: BTreeMap::new(),
+                attachments: Vec::new(),
             }),
             (email, name, message) => Err(Error {
                 email: email.err(),
@@ -39,6 +86,17 @@ impl Contact {
             }),
         }
     }
+
+    pub fn with_fields(self, fields: BTreeMap<String, String>) -> Self {
+        Self { fields, ..self }
+    }
+
+    pub fn with_attachments(self, attachments: Vec<Attachment>) -> Self {
+        Self {
+            attachments,
+            ..self
+        }
+    }
 }
 
 #[cfg(test)]
@@ -47,7 +105,7 @@ mod tests {
 
     #[test]
     fn collects_all_errors() {
-        let contact = Contact::new("", "", "");
+        let contact = Contact::new("", "", "", &ContactPolicy::default());
 
         let expected_errors = Error {
             email: Some(EmailError::IsEmpty),
@@ -60,7 +118,7 @@ mod tests {
 
     #[test]
     fn can_fail_just_email() {
-        let contact = Contact::new("", "good", "good");
+        let contact = Contact::new("", "good", "good", &ContactPolicy::default());
 
         let expected_errors = Error {
             email: Some(EmailError::IsEmpty),
@@ -73,7 +131,7 @@ mod tests {
 
     #[test]
     fn can_fail_just_name() {
-        let contact = Contact::new("good@foo.com", "", "good");
+        let contact = Contact::new("good@foo.com", "", "good", &ContactPolicy::default());
 
         let expected_errors = Error {
             email: None,
@@ -86,7 +144,7 @@ mod tests {
 
     #[test]
     fn can_fail_just_message() {
-        let contact = Contact::new("good@foo.com", "good", "");
+        let contact = Contact::new("good@foo.com", "good", "", &ContactPolicy::default());
 
         let expected_errors = Error {
             email: None,
@@ -99,7 +157,13 @@ mod tests {
 
     #[test]
     fn can_construct_a_contact() {
-        let contact = Contact::new("good@foo.com", "joe", "hello world").unwrap();
+        let contact = Contact::new(
+            "good@foo.com",
+            "joe",
+            "hello world",
+            &ContactPolicy::default(),
+        )
+        .unwrap();
 
         assert_eq!("good@foo.com".to_owned(), contact.email.to_string());
         assert_eq!("joe".to_owned(), contact.name.to_string());
@@ -1,6 +1,11 @@
+mod policy;
+
 use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::length::{LengthError, LengthPolicy};
 
-use unicode_segmentation::UnicodeSegmentation;
+pub use policy::DomainPolicy;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Email(String);
@@ -20,24 +25,144 @@ impl AsRef<str> for Email {
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Error {
     IsEmpty,
-    IsGreaterThan300,
+    TooShort(usize),
+    TooLong(usize),
     IsMissingAtSign,
+    MultipleAtSigns,
+    InvalidLocalPart,
+    InvalidDomain,
+    DomainNotAllowed,
+}
+
+impl From<LengthError> for Error {
+    fn from(error: LengthError) -> Self {
+        match error {
+            LengthError::IsEmpty => Error::IsEmpty,
+            LengthError::TooShort(min) => Error::TooShort(min),
+            LengthError::TooLong(max) => Error::TooLong(max),
+        }
+    }
+}
+
+fn is_atext(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || "!#$%&'*+-/=?^_`{|}~".contains(c)
+        || (!c.is_ascii() && !c.is_control() && !c.is_whitespace())
+}
+
+/// Splits off a leading quoted string, returning it and the rest of the address.
+fn split_quoted(email: &str) -> Option<(&str, &str)> {
+    let mut escaped = false;
+
+    for (index, c) in email.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(email.split_at(index + 1)),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn is_quoted_string(local_part: &str) -> bool {
+    let mut chars = local_part[1..local_part.len() - 1].chars();
+
+    while let Some(c) = chars.next() {
+        let valid = match c {
+            '\\' => chars.next().is_some_and(|c| !c.is_control() || c == '\t'),
+            c => !c.is_control() || c == '\t',
+        };
+
+        if !valid {
+            return false;
+        }
+    }
+
+    true
+}
+
+fn is_dot_atom(local_part: &str) -> bool {
+    local_part
+        .split('.')
+        .all(|atom| !atom.is_empty() && atom.chars().all(is_atext))
+}
+
+fn is_domain_literal(literal: &str) -> bool {
+    match literal.strip_prefix("IPv6:") {
+        Some(ipv6) => ipv6.parse::<Ipv6Addr>().is_ok(),
+        None => literal.parse::<Ipv4Addr>().is_ok(),
+    }
+}
+
+fn is_hostname(domain: &str) -> bool {
+    let ascii = match idna::domain_to_ascii(domain) {
+        Ok(ascii) => ascii,
+        Err(_) => return false,
+    };
+
+    ascii.len() <= 253
+        && ascii.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+fn validate(email: &str) -> Result<(), Error> {
+    let (local_part, rest) = if email.starts_with('"') {
+        split_quoted(email).ok_or(Error::InvalidLocalPart)?
+    } else {
+        email.split_at(email.find('@').unwrap_or(email.len()))
+    };
+
+    let domain = rest.strip_prefix('@').ok_or(Error::InvalidLocalPart)?;
+
+    let valid_local_part = if local_part.starts_with('"') {
+        is_quoted_string(local_part)
+    } else {
+        is_dot_atom(local_part)
+    };
+
+    if local_part.len() > 64 || !valid_local_part {
+        return Err(Error::InvalidLocalPart);
+    }
+
+    if domain.contains('@') {
+        return Err(Error::MultipleAtSigns);
+    }
+
+    let valid_domain = match domain.strip_prefix('[') {
+        Some(literal) => literal.strip_suffix(']').is_some_and(is_domain_literal),
+        None => is_hostname(domain),
+    };
+
+    if valid_domain {
+        Ok(())
+    } else {
+        Err(Error::InvalidDomain)
+    }
 }
 
 impl Email {
-    pub fn new(email: &str) -> Result<Self, Error> {
+    pub fn new(email: &str, policy: &LengthPolicy) -> Result<Self, Error> {
         let email = email.trim();
 
-        if email.is_empty() {
-            Err(Error::IsEmpty)
-        } else if email.graphemes(true).count() > 300 {
-            Err(Error::IsGreaterThan300)
-        } else if !email.contains(|c| c == '@') {
+        policy.check(email)?;
+
+        if !email.contains('@') {
             Err(Error::IsMissingAtSign)
         } else {
-            Ok(Self(email.to_owned()))
+            validate(email).map(|_| Self(email.to_owned()))
         }
     }
+
+    pub fn domain(&self) -> &str {
+        self.0.rsplit('@').next().unwrap_or("")
+    }
 }
 
 #[cfg(test)]
@@ -45,30 +170,91 @@ mod tests {
     use super::*;
     use std::iter;
 
+    const POLICY: LengthPolicy = LengthPolicy { min: 1, max: 300 };
+
     #[test]
     fn does_not_allow_empty_email() {
-        assert_eq!(Err(Error::IsEmpty), Email::new(""));
+        assert_eq!(Err(Error::IsEmpty), Email::new("", &POLICY));
     }
 
     #[test]
     fn does_not_allow_all_whitespace_for_email() {
-        assert_eq!(Err(Error::IsEmpty), Email::new("      "));
+        assert_eq!(Err(Error::IsEmpty), Email::new("      ", &POLICY));
     }
 
     #[test]
     fn does_not_allow_all_more_than_300_characters_for_email() {
-        let long_message = iter::repeat('@').take(301).collect::<String>();
-        assert_eq!(Err(Error::IsGreaterThan300), Email::new(&long_message));
+        let long_message = iter::repeat_n('@', 301).collect::<String>();
+        assert_eq!(Err(Error::TooLong(300)), Email::new(&long_message, &POLICY));
     }
 
     #[test]
     fn does_not_allow_email_to_not_have_at_sign() {
         assert_eq!(
             Err(Error::IsMissingAtSign),
-            Email::new("someemail_at_domain")
+            Email::new("someemail_at_domain", &POLICY)
+        );
+    }
+
+    #[test]
+    fn does_not_allow_more_than_one_at_sign() {
+        assert_eq!(
+            Err(Error::MultipleAtSigns),
+            Email::new("foo@@bar.com", &POLICY)
+        );
+        assert_eq!(
+            Err(Error::MultipleAtSigns),
+            Email::new("foo@bar@baz.com", &POLICY)
         );
     }
 
+    #[test]
+    fn does_not_allow_invalid_local_parts() {
+        let invalid_emails = vec![
+            "@example.com",
+            ".email@example.com",
+            "email.@example.com",
+            "email..email@example.com",
+            "email email@example.com",
+            "(email)@example.com",
+            "\"unterminated@example.com",
+            "\"quoted\"trailing@example.com",
+        ];
+
+        for email in invalid_emails {
+            assert_eq!(
+                Err(Error::InvalidLocalPart),
+                Email::new(email, &POLICY),
+                "{}",
+                email
+            );
+        }
+    }
+
+    #[test]
+    fn does_not_allow_invalid_domains() {
+        let invalid_emails = vec![
+            "a@",
+            "email@-example.com",
+            "email@example-.com",
+            "email@example..com",
+            "email@example.com.",
+            "email@exa_mple.com",
+            "email@[123.123.123]",
+            "email@[IPv6:not-an-ip]",
+            "email@[123.123.123.123",
+        ];
+
+        for email in invalid_emails {
+            assert_eq!(
+                Err(Error::InvalidDomain),
+                Email::new(email, &POLICY),
+                "{}",
+                email
+            );
+        }
+    }
+
     #[test]
     fn accepts_valid_emails() {
         let valid_emails = vec![
@@ -86,12 +272,17 @@ mod tests {
             "email@example.co.jp",
             "firstname-lastname@example.com",
             "“email”@example.com",
+            "\"john doe\"@example.com",
+            "\"at@sign\\\"quote\"@example.com",
+            "email@[IPv6:2001:db8::1]",
+            "email@bücher.example",
+            "用户@例子.广告",
         ];
 
         for email in valid_emails {
             assert_eq!(
                 Ok(email),
-                Email::new(email)
+                Email::new(email, &POLICY)
                     .map(|e| e.to_string())
                     .as_ref()
                     .map(|s| s.as_ref())
@@ -0,0 +1,291 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::{Email, Error};
+
+fn normalize(domain: &str) -> String {
+    let domain = domain.trim().trim_end_matches('.');
+    idna::domain_to_ascii(domain).unwrap_or_else(|_| domain.to_lowercase())
+}
+
+fn domains<'a>(domains: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+    domains
+        .into_iter()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|domain| !domain.is_empty())
+        .map(normalize)
+        .collect()
+}
+
+/// Whether the domain or any of its parent domains is in the list.
+fn matches(list: &HashSet<String>, domain: &str) -> bool {
+    let mut domain = domain;
+
+    loop {
+        if list.contains(domain) {
+            return true;
+        }
+
+        match domain.split_once('.') {
+            Some((_, parent)) => domain = parent,
+            None => return false,
+        }
+    }
+}
+
+/// Identifies a version of the list file, so it is only re-read after it changes.
+type Version = (Option<SystemTime>, u64);
+
+/// How long a change to the list file may go unnoticed, sparing submissions a blocking stat.
+const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Default)]
+struct DisposableList {
+    domains: HashSet<String>,
+    version: Option<Version>,
+    checked: Option<Instant>,
+}
+
+fn version(path: &PathBuf) -> io::Result<Version> {
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.modified().ok(), metadata.len()))
+}
+
+/// Rejects emails from denied or disposable domains, unless their domain is explicitly allowed.
+#[derive(Default)]
+pub struct DomainPolicy {
+    allow: HashSet<String>,
+    deny: HashSet<String>,
+    disposable_list_path: Option<PathBuf>,
+    disposable: RwLock<DisposableList>,
+    recheck_interval: Duration,
+}
+
+impl DomainPolicy {
+    pub fn new(
+        allow: &[String],
+        deny: &[String],
+        disposable_list_path: Option<&str>,
+    ) -> io::Result<Self> {
+        let policy = Self {
+            allow: domains(allow.iter().map(String::as_str)),
+            deny: domains(deny.iter().map(String::as_str)),
+            disposable_list_path: disposable_list_path.map(PathBuf::from),
+            disposable: RwLock::default(),
+            recheck_interval: RECHECK_INTERVAL,
+        };
+
+        if let Some(path) = &policy.disposable_list_path {
+            let version = version(path)?;
+            let contents = fs::read_to_string(path)?;
+
+            *policy.disposable.write().unwrap() = DisposableList {
+                domains: domains(contents.lines()),
+                version: Some(version),
+                checked: Some(Instant::now()),
+            };
+        }
+
+        Ok(policy)
+    }
+
+    /// Whether the list file is due to be checked for changes, marking it checked if so.
+    fn recheck_due(&self) -> bool {
+        let due = |list: &DisposableList| {
+            list.checked
+                .is_none_or(|checked| checked.elapsed() >= self.recheck_interval)
+        };
+
+        if !due(&self.disposable.read().unwrap()) {
+            return false;
+        }
+
+        let mut list = self.disposable.write().unwrap();
+        if !due(&list) {
+            return false;
+        }
+        list.checked = Some(Instant::now());
+        true
+    }
+
+    /// Keeps the previous list when the file can't be read, so a bad deploy doesn't open the gates.
+    fn reload_if_changed(&self) {
+        let path = match &self.disposable_list_path {
+            Some(path) => path,
+            None => return,
+        };
+
+        if !self.recheck_due() {
+            return;
+        }
+
+        let version = match version(path) {
+            Ok(version) => version,
+            Err(error) => {
+                tracing::warn!("Unable to check disposable domain list: {}", error);
+                return;
+            }
+        };
+
+        if self.disposable.read().unwrap().version == Some(version) {
+            return;
+        }
+
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                let domains = domains(contents.lines());
+                tracing::info!("Loaded {} disposable domains.", domains.len());
+
+                let mut list = self.disposable.write().unwrap();
+                list.domains = domains;
+                list.version = Some(version);
+            }
+            Err(error) => tracing::warn!("Unable to reload disposable domain list: {}", error),
+        }
+    }
+
+    pub fn check(&self, email: &Email) -> Result<(), Error> {
+        let domain = normalize(email.domain());
+
+        if matches(&self.allow, &domain) {
+            return Ok(());
+        }
+
+        self.reload_if_changed();
+
+        if matches(&self.deny, &domain)
+            || matches(&self.disposable.read().unwrap().domains, &domain)
+        {
+            Err(Error::DomainNotAllowed)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::contact::LengthPolicy;
+
+    fn email(email: &str) -> Email {
+        Email::new(email, &LengthPolicy::UNLIMITED).unwrap()
+    }
+
+    fn strings(domains: &[&str]) -> Vec<String> {
+        domains.iter().map(|domain| domain.to_string()).collect()
+    }
+
+    fn list_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("{}-{}.txt", name, std::process::id()))
+    }
+
+    #[test]
+    fn denied_domains_include_subdomains() {
+        let policy = DomainPolicy::new(&[], &strings(&["spam.fake"]), None).unwrap();
+
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@Spam.Fake"))
+        );
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@mail.spam.fake"))
+        );
+        assert_eq!(Ok(()), policy.check(&email("bot@notspam.fake")));
+    }
+
+    #[test]
+    fn allowed_domains_override_the_deny_list() {
+        let policy = DomainPolicy::new(
+            &strings(&["good.spam.fake"]),
+            &strings(&["spam.fake"]),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(Ok(()), policy.check(&email("shaggy@good.spam.fake")));
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@spam.fake"))
+        );
+    }
+
+    #[test]
+    fn disposable_list_skips_comments_and_blank_lines() {
+        let path = list_path("disposable-comments");
+        fs::write(
+            &path,
+            "# Throwaway providers\n\nmailinator.com\nGuerrillaMail.com # popular\n",
+        )
+        .unwrap();
+
+        let policy = DomainPolicy::new(&[], &[], path.to_str()).unwrap();
+
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@mailinator.com"))
+        );
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@guerrillamail.com"))
+        );
+        assert_eq!(Ok(()), policy.check(&email("scooby@mystery.van")));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn disposable_list_reloads_when_the_file_changes() {
+        let path = list_path("disposable-reload");
+        fs::write(&path, "mailinator.com\n").unwrap();
+
+        let policy = DomainPolicy {
+            recheck_interval: Duration::ZERO,
+            ..DomainPolicy::new(&[], &[], path.to_str()).unwrap()
+        };
+        assert_eq!(Ok(()), policy.check(&email("bot@tempmail.fake")));
+
+        fs::write(&path, "mailinator.com\ntempmail.fake\n").unwrap();
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@tempmail.fake"))
+        );
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            Err(Error::DomainNotAllowed),
+            policy.check(&email("bot@tempmail.fake"))
+        );
+    }
+
+    #[test]
+    fn disposable_list_is_rechecked_at_most_once_every_interval() {
+        let path = list_path("disposable-recheck");
+        fs::write(&path, "mailinator.com\n").unwrap();
+
+        let policy = DomainPolicy::new(&[], &[], path.to_str()).unwrap();
+
+        fs::write(&path, "mailinator.com\ntempmail.fake\n").unwrap();
+        assert_eq!(Ok(()), policy.check(&email("bot@tempmail.fake")));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn missing_disposable_list_is_an_error() {
+        assert!(DomainPolicy::new(&[], &[], Some("/nonexistent/disposable.txt")).is_err());
+    }
+
+    #[test]
+    fn default_policy_allows_everything() {
+        assert_eq!(
+            Ok(()),
+            DomainPolicy::default().check(&email("bot@mailinator.com"))
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use super::email::Email;
+use super::length::{LengthError, LengthPolicy};
+use crate::settings::{FieldSettings, FieldType};
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    IsEmpty,
+    TooShort(usize),
+    TooLong(usize),
+    TooSmall(i64),
+    TooLarge(i64),
+    InvalidEmail,
+    InvalidPhone,
+    InvalidNumber,
+    InvalidOption,
+    InvalidBoolean,
+    InvalidUrl,
+}
+
+impl From<LengthError> for Error {
+    fn from(error: LengthError) -> Self {
+        match error {
+            LengthError::IsEmpty => Error::IsEmpty,
+            LengthError::TooShort(min) => Error::TooShort(min),
+            LengthError::TooLong(max) => Error::TooLong(max),
+        }
+    }
+}
+
+/// Accepts what checkboxes and json send, so an unchecked box can be left out entirely.
+fn boolean(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => Some(true),
+        "false" | "off" | "no" | "0" | "" => Some(false),
+        _ => None,
+    }
+}
+
+/// Loosely formatted numbers with 7 to 15 digits, the longest E.164 allows.
+fn is_phone(value: &str) -> bool {
+    let digits = value.chars().filter(char::is_ascii_digit).count();
+
+    (7..=15).contains(&digits)
+        && value.char_indices().all(|(index, c)| match c {
+            '+' => index == 0,
+            c => c.is_ascii_digit() || " -().".contains(c),
+        })
+}
+
+fn is_url(value: &str) -> bool {
+    url::Url::parse(value)
+        .map(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+        .unwrap_or(false)
+}
+
+fn number(field: &FieldSettings, value: &str) -> Result<(), Error> {
+    let number = value
+        .parse::<f64>()
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or(Error::InvalidNumber)?;
+
+    match (field.min, field.max) {
+        (Some(min), _) if number < min as f64 => Err(Error::TooSmall(min)),
+        (_, Some(max)) if number > max as f64 => Err(Error::TooLarge(max)),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the normalized value, or `None` when an optional field was left empty.
+fn validate_field(field: &FieldSettings, value: Option<&str>) -> Result<Option<String>, Error> {
+    let value = value.map(str::trim).unwrap_or("");
+
+    if field.kind == FieldType::Boolean {
+        return match boolean(value) {
+            Some(false) if field.required => Err(Error::IsEmpty),
+            Some(checked) => Ok(Some(checked.to_string())),
+            None => Err(Error::InvalidBoolean),
+        };
+    }
+
+    if value.is_empty() {
+        return if field.required {
+            Err(Error::IsEmpty)
+        } else {
+            Ok(None)
+        };
+    }
+
+    let length = LengthPolicy {
+        min: field.min_length.unwrap_or(0),
+        max: field.max_length.unwrap_or(usize::MAX),
+    };
+
+    match field.kind {
+        FieldType::Text => length.check(value)?,
+        FieldType::Email => {
+            length.check(value)?;
+            Email::new(value, &LengthPolicy::UNLIMITED).map_err(|_| Error::InvalidEmail)?;
+        }
+        FieldType::Phone if !is_phone(value) => return Err(Error::InvalidPhone),
+        FieldType::Phone => length.check(value)?,
+        FieldType::Url if !is_url(value) => return Err(Error::InvalidUrl),
+        FieldType::Url => length.check(value)?,
+        FieldType::Number => number(field, value)?,
+        FieldType::Enum if !field.options.iter().any(|option| option == value) => {
+            return Err(Error::InvalidOption)
+        }
+        FieldType::Enum | FieldType::Boolean => {}
+    }
+
+    Ok(Some(value.to_owned()))
+}
+
+/// Validates the fields in the schema, dropping any it doesn't declare. Without a schema every
+/// field is passed through untouched.
+pub fn validate(
+    schema: &[FieldSettings],
+    mut fields: BTreeMap<String, String>,
+) -> Result<BTreeMap<String, String>, BTreeMap<String, Error>> {
+    let mut errors = BTreeMap::new();
+
+    for field in schema {
+        match validate_field(field, fields.get(&field.name).map(String::as_str)) {
+            Ok(Some(value)) => {
+                fields.insert(field.name.to_owned(), value);
+            }
+            Ok(None) => {
+                fields.remove(&field.name);
+            }
+            Err(error) => {
+                errors.insert(field.name.to_owned(), error);
+            }
+        }
+    }
+
+    if !schema.is_empty() {
+        fields.retain(|name, _| schema.iter().any(|field| &field.name == name));
+    }
+
+    if errors.is_empty() {
+        Ok(fields)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::field;
+
+    fn check(field: &FieldSettings, value: &str) -> Result<Option<String>, Error> {
+        validate_field(field, Some(value))
+    }
+
+    #[test]
+    fn optional_fields_may_be_left_out() {
+        let phone = field("phone", FieldType::Phone);
+
+        assert_eq!(Ok(None), validate_field(&phone, None));
+        assert_eq!(Ok(None), check(&phone, "   "));
+    }
+
+    #[test]
+    fn required_fields_may_not_be_empty() {
+        let company = FieldSettings {
+            required: true,
+            ..field("company", FieldType::Text)
+        };
+
+        assert_eq!(Err(Error::IsEmpty), validate_field(&company, None));
+        assert_eq!(Err(Error::IsEmpty), check(&company, " "));
+    }
+
+    #[test]
+    fn text_lengths_are_checked() {
+        let company = FieldSettings {
+            min_length: Some(2),
+            max_length: Some(5),
+            ..field("company", FieldType::Text)
+        };
+
+        assert_eq!(Err(Error::TooShort(2)), check(&company, "A"));
+        assert_eq!(Err(Error::TooLong(5)), check(&company, "Mystery Inc"));
+        assert_eq!(Ok(Some("Acme".to_owned())), check(&company, " Acme "));
+    }
+
+    #[test]
+    fn emails_phones_and_urls_must_be_well_formed() {
+        let email = field("cc", FieldType::Email);
+        let phone = field("phone", FieldType::Phone);
+        let url = field("website", FieldType::Url);
+
+        assert_eq!(Err(Error::InvalidEmail), check(&email, "velma@"));
+        assert!(check(&email, "velma@mystery.van").is_ok());
+
+        assert_eq!(Err(Error::InvalidPhone), check(&phone, "call me"));
+        assert_eq!(Err(Error::InvalidPhone), check(&phone, "555-12"));
+        assert_eq!(Err(Error::InvalidPhone), check(&phone, "555+1234567"));
+        assert!(check(&phone, "+1 (555) 123-4567").is_ok());
+
+        assert_eq!(Err(Error::InvalidUrl), check(&url, "mystery.van"));
+        assert_eq!(Err(Error::InvalidUrl), check(&url, "ftp://mystery.van"));
+        assert!(check(&url, "https://mystery.van/clues").is_ok());
+    }
+
+    #[test]
+    fn numbers_must_be_within_the_range() {
+        let seats = FieldSettings {
+            min: Some(1),
+            max: Some(10),
+            ..field("seats", FieldType::Number)
+        };
+
+        assert_eq!(Err(Error::InvalidNumber), check(&seats, "a few"));
+        assert_eq!(Err(Error::InvalidNumber), check(&seats, "NaN"));
+        assert_eq!(Err(Error::TooSmall(1)), check(&seats, "0"));
+        assert_eq!(Err(Error::TooLarge(10)), check(&seats, "10.5"));
+        assert_eq!(Ok(Some("2.5".to_owned())), check(&seats, "2.5"));
+    }
+
+    #[test]
+    fn enums_only_accept_their_options() {
+        let budget = FieldSettings {
+            options: vec!["small".to_owned(), "large".to_owned()],
+            ..field("budget", FieldType::Enum)
+        };
+
+        assert_eq!(Err(Error::InvalidOption), check(&budget, "huge"));
+        assert_eq!(Ok(Some("large".to_owned())), check(&budget, "large"));
+    }
+
+    #[test]
+    fn booleans_are_normalized_and_required_means_checked() {
+        let newsletter = field("newsletter", FieldType::Boolean);
+        let terms = FieldSettings {
+            required: true,
+            ..field("terms", FieldType::Boolean)
+        };
+
+        assert_eq!(Ok(Some("true".to_owned())), check(&newsletter, "on"));
+        assert_eq!(
+            Ok(Some("false".to_owned())),
+            validate_field(&newsletter, None)
+        );
+        assert_eq!(Err(Error::InvalidBoolean), check(&newsletter, "maybe"));
+        assert_eq!(Err(Error::IsEmpty), validate_field(&terms, None));
+        assert_eq!(Ok(Some("true".to_owned())), check(&terms, "true"));
+    }
+
+    #[test]
+    fn collects_errors_for_every_field_and_drops_undeclared_ones() {
+        let schema = vec![
+            FieldSettings {
+                required: true,
+                ..field("company", FieldType::Text)
+            },
+            field("phone", FieldType::Phone),
+            field("newsletter", FieldType::Boolean),
+        ];
+
+        let mut fields = BTreeMap::new();
+        fields.insert("phone".to_owned(), "nope".to_owned());
+        fields.insert("referral".to_owned(), "friend".to_owned());
+
+        let mut errors = BTreeMap::new();
+        errors.insert("company".to_owned(), Error::IsEmpty);
+        errors.insert("phone".to_owned(), Error::InvalidPhone);
+        assert_eq!(Err(errors), validate(&schema, fields.clone()));
+
+        fields.insert("company".to_owned(), "Mystery Inc".to_owned());
+        fields.insert("phone".to_owned(), "555 123 4567".to_owned());
+
+        let mut expected = fields.clone();
+        expected.remove("referral");
+        expected.insert("newsletter".to_owned(), "false".to_owned());
+        assert_eq!(Ok(expected), validate(&schema, fields));
+    }
+
+    #[test]
+    fn without_a_schema_every_field_is_kept() {
+        let mut fields = BTreeMap::new();
+        fields.insert("referral".to_owned(), "friend".to_owned());
+
+        assert_eq!(Ok(fields.clone()), validate(&[], fields));
+    }
+}
@@ -0,0 +1,61 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Bounds on the number of graphemes in a trimmed field.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LengthPolicy {
+    pub min: usize,
+    pub max: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LengthError {
+    IsEmpty,
+    TooShort(usize),
+    TooLong(usize),
+}
+
+impl LengthPolicy {
+    pub const UNLIMITED: Self = Self {
+        min: 0,
+        max: usize::MAX,
+    };
+
+    /// Empty values are always an error, whatever the minimum.
+    pub fn check(&self, value: &str) -> Result<(), LengthError> {
+        if value.is_empty() {
+            return Err(LengthError::IsEmpty);
+        }
+
+        let length = value.graphemes(true).count();
+
+        if length < self.min {
+            Err(LengthError::TooShort(self.min))
+        } else if length > self.max {
+            Err(LengthError::TooLong(self.max))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_graphemes_within_the_bounds() {
+        let policy = LengthPolicy { min: 2, max: 4 };
+
+        assert_eq!(Err(LengthError::IsEmpty), policy.check(""));
+        assert_eq!(Err(LengthError::TooShort(2)), policy.check("a"));
+        assert_eq!(Ok(()), policy.check("ab"));
+        assert_eq!(Ok(()), policy.check("ye\u{301}s!"));
+        assert_eq!(Err(LengthError::TooLong(4)), policy.check("abcde"));
+    }
+
+    #[test]
+    fn unlimited_only_rejects_empty_values() {
+        assert_eq!(Err(LengthError::IsEmpty), LengthPolicy::UNLIMITED.check(""));
+        assert_eq!(Ok(()), LengthPolicy::UNLIMITED.check("a"));
+    }
+}
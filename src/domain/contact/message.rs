@@ -1,6 +1,6 @@
 use std::fmt;
 
-use unicode_segmentation::UnicodeSegmentation;
+use super::length::{LengthError, LengthPolicy};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Message(String);
@@ -20,20 +20,27 @@ impl AsRef<str> for Message {
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Error {
     IsEmpty,
-    IsGreaterThan2000,
+    TooShort(usize),
+    TooLong(usize),
+}
+
+impl From<LengthError> for Error {
+    fn from(error: LengthError) -> Self {
+        match error {
+            LengthError::IsEmpty => Error::IsEmpty,
+            LengthError::TooShort(min) => Error::TooShort(min),
+            LengthError::TooLong(max) => Error::TooLong(max),
+        }
+    }
 }
 
 impl Message {
-    pub fn new(message: &str) -> Result<Self, Error> {
+    pub fn new(message: &str, policy: &LengthPolicy) -> Result<Self, Error> {
         let message = message.trim();
 
-        if message.is_empty() {
-            Err(Error::IsEmpty)
-        } else if message.graphemes(true).count() > 2000 {
-            Err(Error::IsGreaterThan2000)
-        } else {
-            Ok(Self(message.to_owned()))
-        }
+        policy.check(message)?;
+
+        Ok(Self(message.to_owned()))
     }
 }
 
@@ -43,28 +50,45 @@ mod tests {
 
     use super::*;
 
+    const POLICY: LengthPolicy = LengthPolicy { min: 1, max: 2000 };
+
     #[test]
     fn does_not_allow_empty_messages() {
-        assert_eq!(Err(Error::IsEmpty), Message::new(""))
+        assert_eq!(Err(Error::IsEmpty), Message::new("", &POLICY))
     }
 
     #[test]
     fn does_not_allow_messages_that_are_all_whitespace() {
-        assert_eq!(Err(Error::IsEmpty), Message::new("    "))
+        assert_eq!(Err(Error::IsEmpty), Message::new("    ", &POLICY))
     }
 
     #[test]
     fn does_not_allow_messages_longer_than_2000_characters() {
-        let long_message = iter::repeat('a').take(2001).collect::<String>();
-        assert_eq!(Err(Error::IsGreaterThan2000), Message::new(&long_message));
+        let long_message = iter::repeat_n('a', 2001).collect::<String>();
+        assert_eq!(
+            Err(Error::TooLong(2000)),
+            Message::new(&long_message, &POLICY)
+        );
+    }
+
+    #[test]
+    fn allows_longer_messages_when_configured() {
+        let policy = LengthPolicy {
+            min: 1,
+            max: 10_000,
+        };
+        let long_message = iter::repeat_n('a', 10_000).collect::<String>();
+        assert!(Message::new(&long_message, &policy).is_ok());
     }
 
     #[test]
     fn does_allow_messages_of_200_characters() {
-        let long_message = iter::repeat('a').take(2000).collect::<String>();
+        let long_message = iter::repeat_n('a', 2000).collect::<String>();
         assert_eq!(
             Ok(&long_message),
-            Message::new(&long_message).map(|n| n.to_string()).as_ref()
+            Message::new(&long_message, &POLICY)
+                .map(|n| n.to_string())
+                .as_ref()
         );
     }
 
@@ -72,17 +96,17 @@ mod tests {
     fn trims_messages() {
         assert_eq!(
             Ok("scooby doo".to_owned()),
-            Message::new("  scooby doo   ").map(|n| n.to_string())
+            Message::new("  scooby doo   ", &POLICY).map(|n| n.to_string())
         );
     }
 
     #[test]
     fn does_not_count_leading_and_trailing_whitespace_as_length() {
-        let long_message = iter::repeat('a').take(2000).collect::<String>();
+        let long_message = iter::repeat_n('a', 2000).collect::<String>();
 
         assert_eq!(
             Ok(&long_message),
-            Message::new(&format!("  {}   ", &long_message))
+            Message::new(&format!("  {}   ", &long_message), &POLICY)
                 .map(|n| n.to_string())
                 .as_ref()
         );
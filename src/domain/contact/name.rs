@@ -1,6 +1,6 @@
 use std::fmt;
 
-use unicode_segmentation::UnicodeSegmentation;
+use super::length::{LengthError, LengthPolicy};
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Name(String);
@@ -20,20 +20,27 @@ impl AsRef<str> for Name {
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Error {
     IsEmpty,
-    IsGreaterThan200,
+    TooShort(usize),
+    TooLong(usize),
+}
+
+impl From<LengthError> for Error {
+    fn from(error: LengthError) -> Self {
+        match error {
+            LengthError::IsEmpty => Error::IsEmpty,
+            LengthError::TooShort(min) => Error::TooShort(min),
+            LengthError::TooLong(max) => Error::TooLong(max),
+        }
+    }
 }
 
 impl Name {
-    pub fn new(name: &str) -> Result<Self, Error> {
+    pub fn new(name: &str, policy: &LengthPolicy) -> Result<Self, Error> {
         let name = name.trim();
 
-        if name.is_empty() {
-            Err(Error::IsEmpty)
-        } else if name.graphemes(true).count() > 200 {
-            Err(Error::IsGreaterThan200)
-        } else {
-            Ok(Self(name.to_owned()))
-        }
+        policy.check(name)?;
+
+        Ok(Self(name.to_owned()))
     }
 }
 
@@ -43,28 +50,38 @@ mod tests {
 
     use super::*;
 
+    const POLICY: LengthPolicy = LengthPolicy { min: 1, max: 200 };
+
     #[test]
     fn does_not_allow_empty_names() {
-        assert_eq!(Err(Error::IsEmpty), Name::new(""))
+        assert_eq!(Err(Error::IsEmpty), Name::new("", &POLICY))
     }
 
     #[test]
     fn does_not_allow_names_that_are_all_whitespace() {
-        assert_eq!(Err(Error::IsEmpty), Name::new("    "))
+        assert_eq!(Err(Error::IsEmpty), Name::new("    ", &POLICY))
     }
 
     #[test]
     fn does_not_allow_names_longer_than_200_characters() {
-        let long_name = iter::repeat('a').take(201).collect::<String>();
-        assert_eq!(Err(Error::IsGreaterThan200), Name::new(&long_name));
+        let long_name = iter::repeat_n('a', 201).collect::<String>();
+        assert_eq!(Err(Error::TooLong(200)), Name::new(&long_name, &POLICY));
+    }
+
+    #[test]
+    fn does_not_allow_names_shorter_than_the_minimum() {
+        let policy = LengthPolicy { min: 3, max: 200 };
+        assert_eq!(Err(Error::TooShort(3)), Name::new(" ab ", &policy));
     }
 
     #[test]
     fn does_allow_names_of_200_characters() {
-        let long_name = iter::repeat('a').take(200).collect::<String>();
+        let long_name = iter::repeat_n('a', 200).collect::<String>();
         assert_eq!(
             Ok(&long_name),
-            Name::new(&long_name).map(|n| n.to_string()).as_ref()
+            Name::new(&long_name, &POLICY)
+                .map(|n| n.to_string())
+                .as_ref()
         );
     }
 
@@ -72,17 +89,17 @@ mod tests {
     fn trims_names() {
         assert_eq!(
             Ok("scooby doo".to_owned()),
-            Name::new("  scooby doo   ").map(|n| n.to_string())
+            Name::new("  scooby doo   ", &POLICY).map(|n| n.to_string())
         );
     }
 
     #[test]
     fn does_not_count_leading_and_trailing_whitespace_as_length() {
-        let long_name = iter::repeat('a').take(200).collect::<String>();
+        let long_name = iter::repeat_n('a', 200).collect::<String>();
 
         assert_eq!(
             Ok(&long_name),
-            Name::new(&format!("  {}   ", &long_name))
+            Name::new(&format!("  {}   ", &long_name), &POLICY)
                 .map(|n| n.to_string())
                 .as_ref()
         );
@@ -0,0 +1,11 @@
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Metadata {
+    pub submitted_at: DateTime<Utc>,
+    pub form_id: Option<String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+    pub spam_score: Option<u32>,
+}
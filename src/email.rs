@@ -1,64 +1,1089 @@
+mod spam_daemon;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-use lettre::{transport::smtp::client::Tls, AsyncTransport};
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use lettre::message::header::ContentType;
+use lettre::message::header::{Header, HeaderName};
+use lettre::message::{Mailbox, MessageBuilder, MultiPart, SinglePart};
+use lettre::transport::smtp::{
+    authentication::{Credentials, Mechanism},
+    client::{Certificate, SmtpConnection, Tls, TlsParameters},
+    extension::ClientId,
+};
+use lettre::AsyncTransport;
+use tera::Tera;
 
+use super::domain::attachment::Attachment;
 use super::domain::contact::Contact;
-use super::settings::EmailSettings;
+use super::domain::metadata::Metadata;
+use super::metrics::Metrics;
+use super::notifier::Notifier;
+use super::settings::{AcknowledgementSettings, EmailSettings, FormSettings, SmtpTls};
+use super::spam::Verdict;
+use spam_daemon::SpamDaemon;
+
+struct Templates {
+    subject: &'static str,
+    text: &'static str,
+    html: &'static str,
+    default_subject: &'static str,
+    default_text: &'static str,
+}
+
+const NOTIFICATION: Templates = Templates {
+    subject: "notification/subject.txt",
+    text: "notification/body.txt",
+    html: "notification/body.html",
+    default_subject: "{{ name }} ({{ email }})",
+    default_text: "{{ message }}{% for field in form_fields %}{% if loop.first %}\n{% endif %}\n{{ field.label }}: {{ field.value }}{% endfor %}",
+};
+
+const ACKNOWLEDGEMENT: Templates = Templates {
+    subject: "acknowledgement/subject.txt",
+    text: "acknowledgement/body.txt",
+    html: "acknowledgement/body.html",
+    default_subject: "We received your message",
+    default_text: "Hi {{ name }},\n\nThanks for getting in touch. We received your message and will get back to you soon.\n",
+};
+
+fn tls(settings: &EmailSettings) -> Result<Tls, Box<dyn Error>> {
+    let parameters = || -> Result<TlsParameters, Box<dyn Error>> {
+        let mut builder = TlsParameters::builder(settings.smtp_host.to_owned());
+
+        if let Some(path) = &settings.smtp_ca_cert_path {
+            let pem = std::fs::read(path)?;
+            builder = builder.add_root_certificate(Certificate::from_pem(&pem)?);
+        }
+
+        Ok(builder.build()?)
+    };
+
+    Ok(match settings.smtp_tls {
+        SmtpTls::None => Tls::None,
+        SmtpTls::Opportunistic => Tls::Opportunistic(parameters()?),
+        SmtpTls::Required => Tls::Required(parameters()?),
+        SmtpTls::Implicit => Tls::Wrapper(parameters()?),
+    })
+}
+
+fn credentials(
+    settings: &EmailSettings,
+) -> Result<Option<(Credentials, Vec<Mechanism>)>, &'static str> {
+    match (
+        &settings.smtp_username,
+        &settings.smtp_password,
+        &settings.smtp_xoauth2_token,
+    ) {
+        (None, None, None) => Ok(None),
+        (Some(_), Some(_), Some(_)) => {
+            Err("Only one of smtp_password and smtp_xoauth2_token may be set.")
+        }
+        (Some(username), None, Some(token)) => Ok(Some((
+            Credentials::new(username.to_owned(), token.to_owned()),
+            vec![Mechanism::Xoauth2],
+        ))),
+        (Some(username), Some(password), None) => Ok(Some((
+            Credentials::new(username.to_owned(), password.to_owned()),
+            vec![Mechanism::Plain, Mechanism::Login],
+        ))),
+        (Some(_), None, None) => Err("smtp_username requires smtp_password or smtp_xoauth2_token."),
+        (None, _, _) => Err("smtp_password and smtp_xoauth2_token require smtp_username."),
+    }
+}
+
+fn subject(tera: &Tera, template: &str, context: &tera::Context) -> Result<String, Box<dyn Error>> {
+    let subject = tera.render(template, context)?;
+    Ok(subject.split_whitespace().collect::<Vec<_>>().join(" "))
+}
+
+impl Templates {
+    // Tera autoescapes templates whose names end in .html, so only the html body is escaped.
+    fn load(
+        &self,
+        tera: &mut Tera,
+        subject: &Option<String>,
+        text: &Option<String>,
+        html: &Option<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        let read = |path: &Option<String>, default: &str| match path {
+            Some(path) => std::fs::read_to_string(path),
+            None => Ok(default.to_owned()),
+        };
+
+        tera.add_raw_template(self.subject, &read(subject, self.default_subject)?)?;
+        tera.add_raw_template(self.text, &read(text, self.default_text)?)?;
+
+        if let Some(path) = html {
+            tera.add_raw_template(self.html, &std::fs::read_to_string(path)?)?;
+        }
+
+        Ok(())
+    }
+
+    fn subject(&self, tera: &Tera, context: &tera::Context) -> Result<String, Box<dyn Error>> {
+        subject(tera, self.subject, context)
+    }
+
+    fn body(
+        &self,
+        tera: &Tera,
+        context: &tera::Context,
+        builder: MessageBuilder,
+        attachments: &[Attachment],
+    ) -> Result<lettre::Message, Box<dyn Error>> {
+        let text = tera.render(self.text, context)?;
+        let html = match tera.get_template(self.html) {
+            Ok(_) => Some(tera.render(self.html, context)?),
+            Err(_) => None,
+        };
+
+        if attachments.is_empty() {
+            return Ok(match html {
+                Some(html) => builder.multipart(MultiPart::alternative_plain_html(text, html))?,
+                None => builder.body(text)?,
+            });
+        }
+
+        let body = match html {
+            Some(html) => {
+                MultiPart::mixed().multipart(MultiPart::alternative_plain_html(text, html))
+            }
+            None => MultiPart::mixed().singlepart(SinglePart::plain(text)),
+        };
+
+        let body = attachments.iter().try_fold(body, |body, attachment| {
+            let content_type = ContentType::parse(&attachment.content_type)?;
+            let part = lettre::message::Attachment::new(attachment.filename.to_owned())
+                .body(attachment.content.clone(), content_type);
+            Ok::<_, Box<dyn Error>>(body.singlepart(part))
+        })?;
+
+        Ok(builder.multipart(body)?)
+    }
+}
+
+fn form_subject(id: &str) -> String {
+    format!("forms/{}/subject.txt", id)
+}
+
+fn templates(
+    settings: &EmailSettings,
+    forms: &BTreeMap<String, FormSettings>,
+) -> Result<Tera, Box<dyn Error>> {
+    let mut tera = Tera::default();
+
+    NOTIFICATION.load(
+        &mut tera,
+        &settings.subject_template,
+        &settings.text_template,
+        &settings.html_template,
+    )?;
+
+    if let Some(acknowledgement) = &settings.acknowledgement {
+        ACKNOWLEDGEMENT.load(
+            &mut tera,
+            &acknowledgement.subject_template,
+            &acknowledgement.text_template,
+            &acknowledgement.html_template,
+        )?;
+    }
+
+    for (id, form) in forms {
+        if let Some(path) = &form.subject_template {
+            tera.add_raw_template(&form_subject(id), &std::fs::read_to_string(path)?)?;
+        }
+    }
+
+    Ok(tera)
+}
+
+struct Form {
+    recipients: Vec<String>,
+    subject: Option<String>,
+    /// Names and labels of the schema fields, in the order they are configured.
+    labels: Vec<(String, String)>,
+}
+
+impl Form {
+    fn new(id: &str, settings: &FormSettings) -> Self {
+        Self {
+            recipients: settings.recipients.clone(),
+            subject: settings.subject_template.as_ref().map(|_| form_subject(id)),
+            labels: settings
+                .fields
+                .iter()
+                .map(|field| {
+                    let label = field.label.as_ref().unwrap_or(&field.name);
+                    (field.name.to_owned(), label.to_owned())
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+struct FormField<'a> {
+    name: &'a str,
+    label: &'a str,
+    value: &'a str,
+}
+
+/// Schema fields in their configured order, followed by any others by name.
+fn form_fields<'a>(contact: &'a Contact, form: Option<&'a Form>) -> Vec<FormField<'a>> {
+    let labels = form.map_or(&[][..], |form| &form.labels);
+
+    let configured = labels.iter().filter_map(|(name, label)| {
+        contact
+            .fields
+            .get(name)
+            .map(|value| FormField { name, label, value })
+    });
+
+    let others = contact
+        .fields
+        .iter()
+        .filter(|(name, _)| !labels.iter().any(|(configured, _)| configured == *name))
+        .map(|(name, value)| FormField {
+            name,
+            label: name,
+            value,
+        });
+
+    configured.chain(others).collect()
+}
+
+struct Acknowledgement {
+    from: String,
+    cooldown: Duration,
+    sent: Mutex<HashMap<String, Instant>>,
+}
+
+impl Acknowledgement {
+    fn new(settings: &AcknowledgementSettings) -> Self {
+        Self {
+            from: settings.from.to_owned(),
+            cooldown: Duration::from_secs(settings.cooldown_secs),
+            sent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn start_cooldown(&self, address: &str) -> bool {
+        let now = Instant::now();
+        let mut sent = self
+            .sent
+            .lock()
+            .expect("Acknowledgement cooldowns are poisoned.");
+
+        sent.retain(|_, at| now.duration_since(*at) < self.cooldown);
+
+        match sent.entry(address.to_lowercase()) {
+            std::collections::hash_map::Entry::Occupied(_) => false,
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                entry.insert(now);
+                true
+            }
+        }
+    }
+}
+
+const DEFAULT_SPAM_SUBJECT_TAG: &str = "[SPAM]";
+
+#[derive(Debug, Clone, Copy)]
+struct SpamScore(u32);
+
+impl Header for SpamScore {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("X-Spam-Score")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        Ok(Self(s.trim().parse()?))
+    }
+
+    fn display(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+fn submitter(contact: &Contact) -> Option<Mailbox> {
+    contact
+        .email
+        .as_ref()
+        .parse()
+        .map(|address| Mailbox::new(Some(contact.name.to_string()), address))
+        .map_err(|error| {
+            tracing::warn!("Submitter address can not be used in headers: {}", error);
+        })
+        .ok()
+}
+
+fn context(contact: &Contact, metadata: &Metadata, form: Option<&Form>) -> tera::Context {
+    let mut context = tera::Context::new();
+    context.insert("name", contact.name.as_ref());
+    context.insert("email", contact.email.as_ref());
+    context.insert("message", contact.message.as_ref());
+    context.insert("timestamp", &metadata.submitted_at.to_rfc3339());
+    context.insert("client_ip", &metadata.client_ip);
+    context.insert("user_agent", &metadata.user_agent);
+    context.insert("referer", &metadata.referer);
+    context.insert("form", &metadata.form_id);
+    context.insert("fields", &contact.fields);
+    context.insert("form_fields", &form_fields(contact, form));
+    context
+}
+
+/// Where the smtp transport connects, kept to test the connection outside of sending.
+#[derive(Clone)]
+struct SmtpServer {
+    host: String,
+    port: u16,
+    hello_name: ClientId,
+    tls: Tls,
+    credentials: Option<(Credentials, Vec<Mechanism>)>,
+}
+
+impl SmtpServer {
+    /// Connects, upgrades and authenticates like the transport would, then quits. The async
+    /// transport keeps its connection private, so this uses a blocking one.
+    fn test_connection(&self, timeout: Duration) -> Result<(), lettre::transport::smtp::Error> {
+        let wrapper = match &self.tls {
+            Tls::Wrapper(parameters) => Some(parameters),
+            _ => None,
+        };
+        let mut connection = SmtpConnection::connect(
+            (self.host.as_str(), self.port),
+            Some(timeout),
+            &self.hello_name,
+            wrapper,
+        )?;
+
+        match &self.tls {
+            Tls::Opportunistic(parameters) if connection.can_starttls() => {
+                connection.starttls(parameters, &self.hello_name)?
+            }
+            Tls::Required(parameters) => connection.starttls(parameters, &self.hello_name)?,
+            _ => {}
+        }
+
+        if let Some((credentials, mechanisms)) = &self.credentials {
+            connection.auth(mechanisms, credentials)?;
+        }
+
+        connection.quit()?;
+
+        Ok(())
+    }
+}
+
+/// Keeps concurrent backup dir probes from racing on the same file.
+static PROBES: AtomicU64 = AtomicU64::new(0);
 
 pub struct EmailService {
     smtp: lettre::AsyncSmtpTransport<lettre::Tokio1Executor>,
+    server: SmtpServer,
     file: lettre::AsyncFileTransport<lettre::Tokio1Executor>,
+    backup_dir: PathBuf,
     from: String,
+    reply_to_submitter: bool,
+    from_submitter: bool,
     recipients: Vec<String>,
+    forms: BTreeMap<String, Form>,
+    spam_subject_tag: String,
+    spam_daemon: Option<SpamDaemon>,
+    templates: Tera,
+    acknowledgement: Option<Acknowledgement>,
+    metrics: Metrics,
 }
 
 impl EmailService {
-    pub fn new(settings: EmailSettings) -> Self {
-        let smtp = lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::relay(&settings.smtp_host)
-            .expect("Unable to connect to smtp relay.")
-            .port(settings.smtp_port)
-            .tls(Tls::None)
-            .build();
+    pub fn new(settings: EmailSettings, forms: &BTreeMap<String, FormSettings>) -> Self {
+        let server = SmtpServer {
+            host: settings.smtp_host.to_owned(),
+            port: settings.smtp_port,
+            hello_name: settings
+                .smtp_hello_name
+                .as_ref()
+                .map_or_else(ClientId::default, |name| ClientId::Domain(name.to_owned())),
+            tls: tls(&settings).expect("Unable to configure smtp tls."),
+            credentials: credentials(&settings).expect("Unable to configure smtp credentials."),
+        };
+
+        let mut smtp =
+            lettre::AsyncSmtpTransport::<lettre::Tokio1Executor>::builder_dangerous(&server.host)
+                .port(server.port)
+                .tls(server.tls.clone())
+                .hello_name(server.hello_name.clone());
+
+        if let Some((credentials, mechanisms)) = server.credentials.clone() {
+            smtp = smtp.credentials(credentials).authentication(mechanisms);
+        }
+
+        let smtp = smtp.build();
 
         std::fs::create_dir_all(&settings.backup_dir).expect("Unable to create backup email dir.");
         let file = lettre::AsyncFileTransport::new(&settings.backup_dir);
 
+        let templates = templates(&settings, forms).expect("Unable to load email templates.");
+
         Self {
             smtp,
+            server,
             file,
+            backup_dir: PathBuf::from(&settings.backup_dir),
             from: settings.from,
+            reply_to_submitter: settings.reply_to_submitter,
+            from_submitter: settings.from_submitter,
             recipients: settings.recipients,
+            forms: forms
+                .iter()
+                .map(|(id, form)| (id.to_owned(), Form::new(id, form)))
+                .collect(),
+            spam_subject_tag: settings
+                .spam_subject_tag
+                .unwrap_or_else(|| DEFAULT_SPAM_SUBJECT_TAG.to_owned()),
+            spam_daemon: settings.spam_daemon.map(SpamDaemon::new),
+            templates,
+            acknowledgement: settings
+                .acknowledgement
+                .as_ref()
+                .filter(|acknowledgement| acknowledgement.enabled)
+                .map(Acknowledgement::new),
+            metrics: Metrics::default(),
         }
     }
 
-    #[tracing::instrument(name = "Process contact to send email", skip(self))]
-    pub async fn send(&self, contact: Contact) -> Result<(), Box<dyn Error>> {
-        let builder = lettre::message::Message::builder()
-            .from(self.from.parse()?)
-            .subject(format!("{} ({})", &contact.name, &contact.email));
+    /// Records send latency and failures in the metrics exposed on `/metrics`.
+    pub fn with_metrics(self, metrics: Metrics) -> Self {
+        Self { metrics, ..self }
+    }
+
+    fn build(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<lettre::Message, Box<dyn Error>> {
+        let form = metadata.form_id.as_ref().and_then(|id| {
+            let form = self.forms.get(id);
+            if form.is_none() {
+                tracing::warn!("Form {} is no longer configured, using defaults.", id);
+            }
+            form
+        });
+
+        let context = context(contact, metadata, form);
+
+        let from: Mailbox = self.from.parse()?;
+        let submitter = submitter(contact);
+
+        let recipients = form.map_or(&self.recipients, |form| &form.recipients);
+        let subject_template = form
+            .and_then(|form| form.subject.as_deref())
+            .unwrap_or(NOTIFICATION.subject);
+
+        let mut subject = subject(&self.templates, subject_template, &context)?;
+        let mut builder = lettre::message::Message::builder();
+
+        if let Some(score) = metadata.spam_score {
+            subject = format!("{} {}", self.spam_subject_tag, subject);
+            builder = builder.header(SpamScore(score));
+        }
+
+        builder = builder.subject(subject);
+
+        builder = match &submitter {
+            Some(submitter) if self.from_submitter => builder.from(submitter.clone()).sender(from),
+            _ => builder.from(from),
+        };
+
+        if let Some(submitter) = submitter.filter(|_| self.reply_to_submitter) {
+            builder = builder.reply_to(submitter);
+        }
 
-        let message = self
-            .recipients
+        let builder = recipients
             .iter()
-            .fold::<Result<lettre::message::MessageBuilder, Box<dyn Error>>, _>(
-                Ok(builder),
+            .try_fold::<_, _, Result<MessageBuilder, Box<dyn Error>>>(
+                builder,
                 |builder, recipient| {
-                    builder.and_then(|b| {
-                        let mail_box = recipient.to_owned().parse()?;
-                        Ok(b.to(mail_box))
-                    })
+                    let mail_box = recipient.to_owned().parse()?;
+                    Ok(builder.to(mail_box))
                 },
-            )?
-            .body(contact.message.to_string())?;
+            )?;
+
+        let message =
+            NOTIFICATION.body(&self.templates, &context, builder, &contact.attachments)?;
 
         tracing::info!("Message built.");
 
-        self.file.send(message.clone()).await?;
+        Ok(message)
+    }
+
+    #[tracing::instrument(name = "Classify contact email with spam daemon", skip(self))]
+    pub async fn classify(&self, contact: &Contact, metadata: &Metadata) -> Verdict {
+        let spam_daemon = match &self.spam_daemon {
+            Some(spam_daemon) => spam_daemon,
+            None => return Verdict::Deliver,
+        };
+
+        match self.build(contact, metadata) {
+            Ok(message) => spam_daemon.classify(&message.formatted()).await,
+            Err(error) => {
+                tracing::warn!("Unable to build message for spam daemon: {}", error);
+                Verdict::Deliver
+            }
+        }
+    }
+
+    #[tracing::instrument(name = "Save contact email to file system", skip(self))]
+    pub async fn backup(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        let message = self.build(contact, metadata)?;
+
+        let id = self
+            .metrics
+            .observe_send("file", self.file.send(message))
+            .await?;
         tracing::info!("Message saved to file system.");
 
-        self.smtp.send(message).await?;
+        if !contact.attachments.is_empty() {
+            self.backup_attachments(&id, &contact.attachments).await?;
+            tracing::info!("Attachments saved to file system.");
+        }
+
+        Ok(())
+    }
+
+    /// Saves attachments next to the backup email, in a directory named after it.
+    async fn backup_attachments(
+        &self,
+        id: &str,
+        attachments: &[Attachment],
+    ) -> Result<(), Box<dyn Error>> {
+        let dir = self.backup_dir.join(id);
+        tokio::fs::create_dir_all(&dir).await?;
+
+        let mut names = HashSet::new();
+        for (index, attachment) in attachments.iter().enumerate() {
+            let name = if names.insert(attachment.filename.as_str()) {
+                attachment.filename.to_owned()
+            } else {
+                format!("{}-{}", index, attachment.filename)
+            };
+
+            tokio::fs::write(dir.join(name), &attachment.content).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn test_connection(&self, timeout: Duration) -> Result<(), Box<dyn Error>> {
+        let server = self.server.clone();
+        tokio::task::spawn_blocking(move || server.test_connection(timeout)).await??;
+
+        Ok(())
+    }
+
+    /// Writes and removes a probe file, as backups fail if the directory isn't writable.
+    pub async fn test_backup_dir(&self) -> std::io::Result<()> {
+        let probe = self.backup_dir.join(format!(
+            ".ready-{}-{}",
+            std::process::id(),
+            PROBES.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        tokio::fs::write(&probe, b"").await?;
+        tokio::fs::remove_file(&probe).await
+    }
+
+    #[tracing::instrument(name = "Send contact email via smtp", skip(self))]
+    pub async fn relay(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        let message = self.build(contact, metadata)?;
+
+        self.metrics
+            .observe_send("smtp", self.smtp.send(message))
+            .await?;
         tracing::info!("Message sent via smtp.");
 
         Ok(())
     }
+
+    fn build_acknowledgement(
+        &self,
+        acknowledgement: &Acknowledgement,
+        submitter: Mailbox,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<lettre::Message, Box<dyn Error>> {
+        let form = metadata.form_id.as_ref().and_then(|id| self.forms.get(id));
+        let context = context(contact, metadata, form);
+
+        let builder = lettre::message::Message::builder()
+            .from(acknowledgement.from.parse()?)
+            .to(submitter)
+            .subject(ACKNOWLEDGEMENT.subject(&self.templates, &context)?);
+
+        ACKNOWLEDGEMENT.body(&self.templates, &context, builder, &[])
+    }
+
+    #[tracing::instrument(name = "Send acknowledgement email via smtp", skip(self))]
+    pub async fn acknowledge(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        let acknowledgement = match &self.acknowledgement {
+            Some(acknowledgement) => acknowledgement,
+            None => return Ok(()),
+        };
+
+        let submitter = match submitter(contact) {
+            Some(submitter) => submitter,
+            None => return Ok(()),
+        };
+
+        // Spam usually forges its sender, so acknowledging it would mail whoever's address was
+        // used.
+        if metadata.spam_score.is_some() {
+            tracing::info!("Acknowledgement skipped, submission was flagged as spam.");
+            return Ok(());
+        }
+
+        if !acknowledgement.start_cooldown(contact.email.as_ref()) {
+            tracing::info!("Acknowledgement skipped, address is cooling down.");
+            return Ok(());
+        }
+
+        let message = self.build_acknowledgement(acknowledgement, submitter, contact, metadata)?;
+
+        self.metrics
+            .observe_send("smtp", self.smtp.send(message))
+            .await?;
+        tracing::info!("Acknowledgement sent via smtp.");
+
+        Ok(())
+    }
+}
+
+impl Notifier for EmailService {
+    fn name(&self) -> &str {
+        "email"
+    }
+
+    fn notify<'a>(
+        &'a self,
+        _: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        self.relay(contact, metadata).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::contact::ContactPolicy;
+    use crate::fixtures::{contact, field, metadata};
+    use crate::settings::{FieldSettings, FieldType};
+
+    fn settings() -> EmailSettings {
+        EmailSettings {
+            smtp_host: "localhost".to_owned(),
+            smtp_port: 1025,
+            smtp_tls: SmtpTls::None,
+            smtp_username: None,
+            smtp_password: None,
+            smtp_xoauth2_token: None,
+            smtp_ca_cert_path: None,
+            smtp_hello_name: None,
+            subject_template: None,
+            text_template: None,
+            html_template: None,
+            spam_subject_tag: None,
+            spam_daemon: None,
+            mailhog_host: "localhost".to_owned(),
+            mailhog_port: 8025,
+            from: "noreply@contact-api.fake".to_owned(),
+            reply_to_submitter: false,
+            from_submitter: false,
+            recipients: vec!["bob@fake.fake".to_owned()],
+            backup_dir: std::env::temp_dir()
+                .join("contact-api-test-emails")
+                .to_string_lossy()
+                .into_owned(),
+            acknowledgement: None,
+        }
+    }
+
+    fn template(content: &str) -> Option<String> {
+        let path = std::env::temp_dir().join(format!("template-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, content).unwrap();
+        Some(path.to_string_lossy().into_owned())
+    }
+
+    fn render(settings: EmailSettings, message: &str) -> String {
+        let contact = Contact::new(
+            "scooby@mystery.van",
+            "Shaggy",
+            message,
+            &ContactPolicy::default(),
+        )
+        .unwrap();
+        let metadata = Metadata {
+            form_id: None,
+            ..metadata()
+        };
+
+        let message = EmailService::new(settings, &BTreeMap::new())
+            .build(&contact, &metadata)
+            .unwrap();
+
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    fn render_form(forms: BTreeMap<String, FormSettings>, form_id: &str) -> String {
+        let mut fields = BTreeMap::new();
+        fields.insert("order_number".to_owned(), "1969".to_owned());
+        let contact = contact().with_fields(fields);
+        let metadata = Metadata {
+            form_id: Some(form_id.to_owned()),
+            ..metadata()
+        };
+
+        let message = EmailService::new(settings(), &forms)
+            .build(&contact, &metadata)
+            .unwrap();
+
+        String::from_utf8(message.formatted()).unwrap()
+    }
+
+    #[test]
+    fn maps_tls_modes() {
+        let modes = vec![
+            (SmtpTls::None, "None"),
+            (SmtpTls::Opportunistic, "Opportunistic"),
+            (SmtpTls::Required, "Required"),
+            (SmtpTls::Implicit, "Wrapper"),
+        ];
+
+        for (smtp_tls, expected) in modes {
+            let settings = EmailSettings {
+                smtp_tls,
+                ..settings()
+            };
+
+            assert_eq!(expected, format!("{:?}", tls(&settings).unwrap()));
+        }
+    }
+
+    #[test]
+    fn fails_on_missing_ca_bundle() {
+        let settings = EmailSettings {
+            smtp_tls: SmtpTls::Required,
+            smtp_ca_cert_path: Some("./does-not-exist.pem".to_owned()),
+            ..settings()
+        };
+
+        assert!(tls(&settings).is_err());
+    }
+
+    #[test]
+    fn no_credentials_by_default() {
+        assert_eq!(None, credentials(&settings()).unwrap());
+    }
+
+    #[test]
+    fn password_uses_plain_or_login() {
+        let settings = EmailSettings {
+            smtp_username: Some("shaggy".to_owned()),
+            smtp_password: Some("scooby snacks".to_owned()),
+            ..settings()
+        };
+
+        assert_eq!(
+            Some((
+                Credentials::new("shaggy".to_owned(), "scooby snacks".to_owned()),
+                vec![Mechanism::Plain, Mechanism::Login]
+            )),
+            credentials(&settings).unwrap()
+        );
+    }
+
+    #[test]
+    fn token_uses_xoauth2() {
+        let settings = EmailSettings {
+            smtp_username: Some("shaggy".to_owned()),
+            smtp_xoauth2_token: Some("token".to_owned()),
+            ..settings()
+        };
+
+        assert_eq!(
+            Some((
+                Credentials::new("shaggy".to_owned(), "token".to_owned()),
+                vec![Mechanism::Xoauth2]
+            )),
+            credentials(&settings).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_credentials() {
+        let missing_secret = EmailSettings {
+            smtp_username: Some("shaggy".to_owned()),
+            ..settings()
+        };
+        let missing_username = EmailSettings {
+            smtp_password: Some("scooby snacks".to_owned()),
+            ..settings()
+        };
+        let both_secrets = EmailSettings {
+            smtp_username: Some("shaggy".to_owned()),
+            smtp_password: Some("scooby snacks".to_owned()),
+            smtp_xoauth2_token: Some("token".to_owned()),
+            ..settings()
+        };
+
+        assert!(credentials(&missing_secret).is_err());
+        assert!(credentials(&missing_username).is_err());
+        assert!(credentials(&both_secrets).is_err());
+    }
+
+    #[test]
+    fn renders_default_subject_and_body() {
+        let email = render(settings(), "Zoinks!");
+
+        assert!(email.contains("Subject: Shaggy (scooby@mystery.van)\r\n"));
+        assert!(email.ends_with("\r\n\r\nZoinks!"));
+        assert!(!email.contains("multipart"));
+    }
+
+    #[test]
+    fn renders_configured_templates_with_metadata() {
+        let settings = EmailSettings {
+            subject_template: template("Contact from {{ name }}\n via {{ referer }}\n"),
+            text_template: template(
+                "{{ message }} / {{ email }} / {{ client_ip }} / {{ timestamp }}",
+            ),
+            ..settings()
+        };
+
+        let email = render(settings, "Zoinks!");
+
+        assert!(email.contains("Subject: Contact from Shaggy via https://mystery.van/contact\r\n"));
+        assert!(
+            email.contains("Zoinks! / scooby@mystery.van / 127.0.0.1 / 2021-06-01T00:00:00+00:00")
+        );
+    }
+
+    #[test]
+    fn html_template_is_escaped_and_sent_as_alternative() {
+        let settings = EmailSettings {
+            html_template: template("<p>{{ message }}</p>"),
+            ..settings()
+        };
+
+        let email = render(settings, "<b>Zoinks</b> & Jinkies");
+
+        assert!(email.contains("Content-Type: multipart/alternative"));
+        assert!(email.contains("<b>Zoinks</b> & Jinkies"));
+        assert!(email.contains("<p>&lt;b&gt;Zoinks&lt;&#x2F;b&gt; &amp; Jinkies</p>"));
+    }
+
+    #[test]
+    fn spam_scores_tag_the_subject_and_headers() {
+        let contact = contact();
+        let metadata = Metadata {
+            spam_score: Some(7),
+            ..metadata()
+        };
+
+        let message = EmailService::new(settings(), &BTreeMap::new())
+            .build(&contact, &metadata)
+            .unwrap();
+        let email = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(email.contains("Subject: [SPAM] Shaggy (scooby@mystery.van)\r\n"));
+        assert!(email.contains("X-Spam-Score: 7\r\n"));
+    }
+
+    #[test]
+    fn headers_only_name_the_service_by_default() {
+        let email = render(settings(), "Zoinks!");
+
+        assert!(email.contains("From: noreply@contact-api.fake\r\n"));
+        assert!(!email.contains("Reply-To:"));
+        assert!(!email.contains("Sender:"));
+    }
+
+    #[test]
+    fn replies_go_to_the_submitter() {
+        let settings = EmailSettings {
+            reply_to_submitter: true,
+            ..settings()
+        };
+
+        let email = render(settings, "Zoinks!");
+
+        assert!(email.contains("From: noreply@contact-api.fake\r\n"));
+        assert!(email.contains("Reply-To: Shaggy <scooby@mystery.van>\r\n"));
+    }
+
+    #[test]
+    fn submitter_can_be_the_sender() {
+        let settings = EmailSettings {
+            from_submitter: true,
+            ..settings()
+        };
+
+        let email = render(settings, "Zoinks!");
+
+        assert!(email.contains("From: Shaggy <scooby@mystery.van>\r\n"));
+        assert!(email.contains("Sender: noreply@contact-api.fake\r\n"));
+    }
+
+    #[test]
+    fn forms_use_their_own_recipients_and_subject() {
+        let mut forms = BTreeMap::new();
+        forms.insert(
+            "support".to_owned(),
+            FormSettings {
+                recipients: vec!["help@fake.fake".to_owned()],
+                subject_template: template("[{{ form }}] Order {{ fields.order_number }}"),
+                required_fields: vec!["order_number".to_owned()],
+                fields: Vec::new(),
+                redirect_to: None,
+            },
+        );
+
+        let email = render_form(forms, "support");
+
+        assert!(email.contains("To: help@fake.fake\r\n"));
+        assert!(email.contains("Subject: [support] Order 1969\r\n"));
+    }
+
+    #[test]
+    fn default_body_lists_fields_in_schema_order_with_labels() {
+        let field = |name: &str, label: Option<&str>| FieldSettings {
+            label: label.map(str::to_owned),
+            ..field(name, FieldType::Text)
+        };
+
+        let mut forms = BTreeMap::new();
+        forms.insert(
+            "support".to_owned(),
+            FormSettings {
+                recipients: vec!["help@fake.fake".to_owned()],
+                subject_template: None,
+                required_fields: Vec::new(),
+                fields: vec![
+                    field("order_number", Some("Order number")),
+                    field("company", None),
+                ],
+                redirect_to: None,
+            },
+        );
+
+        let email = render_form(forms, "support");
+
+        assert!(email.ends_with("\r\n\r\nZoinks!\r\n\r\nOrder number: 1969"));
+    }
+
+    #[test]
+    fn unknown_forms_fall_back_to_the_defaults() {
+        let email = render_form(BTreeMap::new(), "support");
+
+        assert!(email.contains("To: bob@fake.fake\r\n"));
+        assert!(email.contains("Subject: Shaggy (scooby@mystery.van)\r\n"));
+    }
+
+    fn acknowledgement_settings() -> AcknowledgementSettings {
+        AcknowledgementSettings {
+            enabled: true,
+            from: "thanks@contact-api.fake".to_owned(),
+            subject_template: None,
+            text_template: None,
+            html_template: None,
+            cooldown_secs: 3600,
+        }
+    }
+
+    #[test]
+    fn acknowledgement_is_addressed_to_the_submitter() {
+        let settings = EmailSettings {
+            acknowledgement: Some(AcknowledgementSettings {
+                html_template: template("<p>Thanks, {{ name }}!</p>"),
+                ..acknowledgement_settings()
+            }),
+            ..settings()
+        };
+
+        let service = EmailService::new(settings, &BTreeMap::new());
+        let contact = contact();
+        let metadata = metadata();
+
+        let message = service
+            .build_acknowledgement(
+                service.acknowledgement.as_ref().unwrap(),
+                submitter(&contact).unwrap(),
+                &contact,
+                &metadata,
+            )
+            .unwrap();
+        let email = String::from_utf8(message.formatted()).unwrap();
+
+        assert!(email.contains("From: thanks@contact-api.fake\r\n"));
+        assert!(email.contains("To: Shaggy <scooby@mystery.van>\r\n"));
+        assert!(email.contains("Subject: We received your message\r\n"));
+        assert!(email.contains("Hi Shaggy,"));
+        assert!(email.contains("<p>Thanks, Shaggy!</p>"));
+        assert!(!email.contains("Zoinks!"));
+    }
+
+    #[test]
+    fn disabled_acknowledgements_are_not_configured() {
+        let settings = EmailSettings {
+            acknowledgement: Some(AcknowledgementSettings {
+                enabled: false,
+                ..acknowledgement_settings()
+            }),
+            ..settings()
+        };
+
+        assert!(EmailService::new(settings, &BTreeMap::new())
+            .acknowledgement
+            .is_none());
+    }
+
+    #[test]
+    fn acknowledgements_cool_down_per_address() {
+        let acknowledgement = Acknowledgement::new(&acknowledgement_settings());
+
+        assert!(acknowledgement.start_cooldown("scooby@mystery.van"));
+        assert!(!acknowledgement.start_cooldown("scooby@mystery.van"));
+        assert!(!acknowledgement.start_cooldown("Scooby@Mystery.Van"));
+        assert!(acknowledgement.start_cooldown("shaggy@mystery.van"));
+    }
+
+    #[test]
+    fn acknowledgements_can_be_sent_again_after_cooling_down() {
+        let acknowledgement = Acknowledgement::new(&AcknowledgementSettings {
+            cooldown_secs: 0,
+            ..acknowledgement_settings()
+        });
+
+        assert!(acknowledgement.start_cooldown("scooby@mystery.van"));
+        assert!(acknowledgement.start_cooldown("scooby@mystery.van"));
+    }
 }
@@ -0,0 +1,165 @@
+use std::error::Error;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use crate::settings::{SpamDaemonKind, SpamDaemonSettings};
+use crate::spam::Verdict;
+
+#[derive(serde::Deserialize)]
+struct RspamdResponse {
+    score: f64,
+}
+
+/// Parses the score out of a spamd `CHECK` response, e.g. `Spam: True ; 15.0 / 5.0`.
+fn spamd_score(response: &str) -> Result<f64, Box<dyn Error>> {
+    let mut lines = response.lines();
+
+    let status = lines.next().ok_or("Empty spamd response.")?;
+    if status.split_whitespace().nth(1) != Some("0") {
+        return Err(format!("Spamd failed: {}", status).into());
+    }
+
+    let spam = lines
+        .find_map(|line| line.strip_prefix("Spam:"))
+        .ok_or("Spamd response is missing the Spam header.")?;
+
+    let score = spam
+        .split(';')
+        .nth(1)
+        .and_then(|scores| scores.split('/').next())
+        .ok_or("Spamd Spam header is malformed.")?;
+
+    Ok(score.trim().parse()?)
+}
+
+pub struct SpamDaemon {
+    settings: SpamDaemonSettings,
+    client: reqwest::Client,
+}
+
+impl SpamDaemon {
+    pub fn new(settings: SpamDaemonSettings) -> Self {
+        Self {
+            settings,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    async fn rspamd(&self, message: &[u8]) -> Result<f64, Box<dyn Error>> {
+        let url = format!(
+            "http://{}:{}/checkv2",
+            self.settings.host, self.settings.port
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .body(message.to_vec())
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<RspamdResponse>()
+            .await?;
+
+        Ok(response.score)
+    }
+
+    async fn spamd(&self, message: &[u8]) -> Result<f64, Box<dyn Error>> {
+        let mut stream =
+            TcpStream::connect((self.settings.host.as_str(), self.settings.port)).await?;
+
+        let headers = format!(
+            "CHECK SPAMC/1.5\r\nContent-length: {}\r\n\r\n",
+            message.len()
+        );
+        stream.write_all(headers.as_bytes()).await?;
+        stream.write_all(message).await?;
+        stream.shutdown().await?;
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await?;
+
+        spamd_score(&response)
+    }
+
+    async fn score(&self, message: &[u8]) -> Result<f64, Box<dyn Error>> {
+        let score = async {
+            match self.settings.kind {
+                SpamDaemonKind::Rspamd => self.rspamd(message).await,
+                SpamDaemonKind::Spamd => self.spamd(message).await,
+            }
+        };
+
+        tokio::time::timeout(Duration::from_secs(self.settings.timeout_secs), score)
+            .await
+            .map_err(|_| "Spam daemon timed out.")?
+    }
+
+    fn verdict(&self, score: f64) -> Verdict {
+        let points = score.max(0.0).round() as u32;
+
+        if score >= f64::from(self.settings.reject_threshold) {
+            Verdict::Reject(points)
+        } else if score >= f64::from(self.settings.quarantine_threshold) {
+            Verdict::Quarantine(points)
+        } else if score >= f64::from(self.settings.tag_threshold) {
+            Verdict::Tag(points)
+        } else {
+            Verdict::Deliver
+        }
+    }
+
+    /// Delivers the message when the daemon can't be reached rather than losing it.
+    pub async fn classify(&self, message: &[u8]) -> Verdict {
+        match self.score(message).await {
+            Ok(score) => {
+                tracing::info!("Spam daemon scored message {}.", score);
+                self.verdict(score)
+            }
+            Err(error) => {
+                tracing::warn!("Unable to classify message with spam daemon: {}", error);
+                Verdict::Deliver
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_spamd_scores() {
+        let response = "SPAMD/1.5 0 EX_OK\r\nContent-length: 0\r\nSpam: True ; 15.2 / 5.0\r\n\r\n";
+
+        assert_eq!(15.2, spamd_score(response).unwrap());
+    }
+
+    #[test]
+    fn rejects_spamd_failures() {
+        assert!(spamd_score("SPAMD/1.0 76 Bad header line\r\n").is_err());
+        assert!(spamd_score("SPAMD/1.5 0 EX_OK\r\n\r\n").is_err());
+        assert!(spamd_score("").is_err());
+    }
+
+    #[test]
+    fn thresholds_decide_the_verdict() {
+        let daemon = SpamDaemon::new(SpamDaemonSettings {
+            kind: SpamDaemonKind::Rspamd,
+            host: "localhost".to_owned(),
+            port: 11333,
+            timeout_secs: 1,
+            tag_threshold: 5,
+            quarantine_threshold: 10,
+            reject_threshold: 15,
+        });
+
+        assert_eq!(Verdict::Deliver, daemon.verdict(-2.0));
+        assert_eq!(Verdict::Deliver, daemon.verdict(4.9));
+        assert_eq!(Verdict::Tag(5), daemon.verdict(5.2));
+        assert_eq!(Verdict::Quarantine(10), daemon.verdict(10.0));
+        assert_eq!(Verdict::Reject(21), daemon.verdict(20.6));
+    }
+}
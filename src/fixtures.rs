@@ -0,0 +1,59 @@
+//! Submissions and settings shared by unit tests, varied with struct update syntax.
+
+use std::collections::BTreeMap;
+
+use chrono::{TimeZone, Utc};
+
+use super::domain::contact::{Contact, ContactPolicy};
+use super::domain::metadata::Metadata;
+use super::settings::{FieldSettings, FieldType};
+
+pub fn contact() -> Contact {
+    Contact::new(
+        "scooby@mystery.van",
+        "Shaggy",
+        "Zoinks!",
+        &ContactPolicy::default(),
+    )
+    .unwrap()
+}
+
+/// A contact full of the characters chat markup needs escaped.
+pub fn markup_contact() -> Contact {
+    let mut fields = BTreeMap::new();
+    fields.insert("company".to_owned(), "Mystery <Inc>".to_owned());
+
+    Contact::new(
+        "scooby@mystery.van",
+        "Shaggy & Scooby",
+        "Zoinks! *Like*, there's a <ghost>!",
+        &ContactPolicy::default(),
+    )
+    .unwrap()
+    .with_fields(fields)
+}
+
+pub fn metadata() -> Metadata {
+    Metadata {
+        submitted_at: Utc.timestamp_millis_opt(1_622_505_600_000).unwrap(),
+        form_id: Some("support".to_owned()),
+        client_ip: Some("127.0.0.1".to_owned()),
+        user_agent: Some("Mystery Machine".to_owned()),
+        referer: Some("https://mystery.van/contact".to_owned()),
+        spam_score: None,
+    }
+}
+
+pub fn field(name: &str, kind: FieldType) -> FieldSettings {
+    FieldSettings {
+        name: name.to_owned(),
+        label: None,
+        kind,
+        required: false,
+        options: Vec::new(),
+        min_length: None,
+        max_length: None,
+        min: None,
+        max: None,
+    }
+}
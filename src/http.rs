@@ -1,29 +1,113 @@
+mod admin_auth;
+mod client_ip;
+mod rate_limit;
+mod request_metrics;
 mod routes;
 
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use actix_web::{dev::Server, web};
 use actix_web::{App, HttpServer};
 use tracing_actix_web::TracingLogger;
 
+use super::captcha::Captcha;
+use super::domain::attachment::AttachmentPolicy;
+use super::domain::contact::{email::DomainPolicy, ContactPolicy};
 use super::email::EmailService;
-use super::settings::HttpSettings;
+use super::metrics::Metrics;
+use super::outbox::Outbox;
+use super::protection::BotProtection;
+use super::readiness::Readiness;
+use super::settings::{FormSettings, HttpSettings};
+use super::spam::SpamFilter;
+use super::storage::Storage;
+use admin_auth::AdminToken;
+use client_ip::TrustedProxies;
+use rate_limit::RateLimiter;
+use request_metrics::RequestMetrics;
 
 pub struct HttpApp {
     pub server: Server,
     pub port: u16,
 }
 
-pub fn start(settings: HttpSettings, email_service: EmailService) -> std::io::Result<HttpApp> {
+#[allow(clippy::too_many_arguments)]
+pub fn start(
+    settings: HttpSettings,
+    forms: BTreeMap<String, FormSettings>,
+    protection: BotProtection,
+    captcha: Captcha,
+    spam_filter: SpamFilter,
+    contact_policy: ContactPolicy,
+    domain_policy: DomainPolicy,
+    attachment_policy: AttachmentPolicy,
+    email_service: Arc<EmailService>,
+    outbox: Outbox,
+    storage: Option<Storage>,
+    metrics: Metrics,
+    readiness: Readiness,
+) -> std::io::Result<HttpApp> {
     let listener = settings.tcp_listener()?;
 
     let port = listener.local_addr()?.port();
 
-    let email_service = web::Data::new(email_service);
+    let email_service = web::Data::from(email_service);
+    let outbox = web::Data::new(outbox);
+    let admin_token = match (&settings.admin, &storage) {
+        (Some(admin), Some(_)) => Some(web::Data::new(
+            AdminToken::new(&admin.token).map_err(std::io::Error::other)?,
+        )),
+        (Some(_), None) => {
+            tracing::warn!("The admin api needs storage to be configured, disabling it.");
+            None
+        }
+        (None, _) => None,
+    };
+    let storage = storage.map(web::Data::new);
+    let forms = web::Data::new(forms);
+    let protection = web::Data::new(protection);
+    let captcha = web::Data::new(captcha);
+    let spam_filter = web::Data::new(spam_filter);
+    let contact_policy = web::Data::new(contact_policy);
+    let domain_policy = web::Data::new(domain_policy);
+    let attachment_policy = web::Data::new(attachment_policy);
+    let rate_limiter = web::Data::new(RateLimiter::new(settings.rate_limit.as_ref()));
+    let trusted_proxies = web::Data::new(TrustedProxies(settings.trusted_proxies));
+    let request_metrics = metrics.clone();
+    let metrics = web::Data::new(metrics);
+    let readiness = web::Data::new(readiness);
 
     let server = HttpServer::new(move || {
-        App::new()
+        let app = App::new()
+            .wrap(RequestMetrics(request_metrics.clone()))
             .wrap(TracingLogger::default())
             .configure(routes::configure)
             .app_data(email_service.clone())
+            .app_data(outbox.clone())
+            .app_data(forms.clone())
+            .app_data(protection.clone())
+            .app_data(captcha.clone())
+            .app_data(spam_filter.clone())
+            .app_data(contact_policy.clone())
+            .app_data(domain_policy.clone())
+            .app_data(attachment_policy.clone())
+            .app_data(rate_limiter.clone())
+            .app_data(trusted_proxies.clone())
+            .app_data(metrics.clone())
+            .app_data(readiness.clone());
+
+        let app = match &storage {
+            Some(storage) => app.app_data(storage.clone()),
+            None => app,
+        };
+
+        match &admin_token {
+            Some(token) => app
+                .app_data(token.clone())
+                .configure(routes::configure_admin),
+            None => app,
+        }
     })
     .listen(listener)?
     .run();
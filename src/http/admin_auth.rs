@@ -0,0 +1,110 @@
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web::Data,
+    Error, HttpResponse,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use futures_util::FutureExt;
+use sha2::{Digest, Sha256};
+
+/// Compared as digests, so the time taken doesn't reveal how much of a guess was right.
+pub struct AdminToken([u8; 32]);
+
+impl AdminToken {
+    /// Refuses a blank token, which an empty bearer token would match.
+    pub fn new(token: &str) -> Result<Self, &'static str> {
+        if token.trim().is_empty() {
+            return Err("The admin token may not be empty.");
+        }
+
+        Ok(Self(Sha256::digest(token.as_bytes()).into()))
+    }
+
+    fn matches(&self, token: &str) -> bool {
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        self.0 == digest
+    }
+}
+
+fn bearer_token(req: &ServiceRequest) -> Option<&str> {
+    req.headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+/// Rejects requests without the app's `AdminToken` as a bearer token.
+pub struct AdminAuth;
+
+impl<S> Transform<S, ServiceRequest> for AdminAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Transform = AdminAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AdminAuthMiddleware { service }))
+    }
+}
+
+pub struct AdminAuthMiddleware<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for AdminAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let authorized = match (req.app_data::<Data<AdminToken>>(), bearer_token(&req)) {
+            (Some(expected), Some(token)) => expected.matches(token),
+            _ => false,
+        };
+
+        if authorized {
+            return self.service.call(req).boxed_local();
+        }
+
+        tracing::warn!("Unauthorized admin request.");
+        let response = req.into_response(
+            HttpResponse::Unauthorized()
+                .insert_header((header::WWW_AUTHENTICATE, "Bearer"))
+                .finish(),
+        );
+        ready(Ok(response)).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        let token = AdminToken::new("mystery-machine").unwrap();
+
+        assert!(token.matches("mystery-machine"));
+        assert!(!token.matches("mystery-machin"));
+        assert!(!token.matches(""));
+    }
+
+    #[test]
+    fn blank_tokens_are_refused() {
+        assert!(AdminToken::new("").is_err());
+        assert!(AdminToken::new("  ").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+use std::net::{IpAddr, SocketAddr};
+
+use actix_web::{http::HeaderMap, web::Data};
+
+/// Proxies whose `X-Forwarded-For` header is believed when finding the client ip.
+pub struct TrustedProxies(pub Vec<IpAddr>);
+
+impl TrustedProxies {
+    fn client_ip(&self, peer: IpAddr, forwarded_for: &[IpAddr]) -> IpAddr {
+        let mut client = peer;
+
+        // Walk back from the closest hop until one is not ours, as earlier entries can be forged.
+        for hop in std::iter::once(peer).chain(forwarded_for.iter().rev().copied()) {
+            client = hop;
+            if !self.0.contains(&hop) {
+                break;
+            }
+        }
+
+        client
+    }
+}
+
+fn forwarded_for(headers: &HeaderMap) -> Vec<IpAddr> {
+    headers
+        .get_all("x-forwarded-for")
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|hop| hop.trim().parse().ok())
+        .collect()
+}
+
+pub fn client_ip(
+    peer_addr: Option<SocketAddr>,
+    headers: &HeaderMap,
+    proxies: Option<&Data<TrustedProxies>>,
+) -> Option<IpAddr> {
+    let peer = peer_addr?.ip();
+
+    match proxies {
+        Some(proxies) if proxies.0.contains(&peer) => {
+            Some(proxies.client_ip(peer, &forwarded_for(headers)))
+        }
+        _ => Some(peer),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn untrusted_peers_are_the_client() {
+        let proxies = TrustedProxies(vec![ip("10.0.0.1")]);
+
+        assert_eq!(
+            ip("203.0.113.9"),
+            proxies.client_ip(ip("203.0.113.9"), &[ip("198.51.100.1")])
+        );
+    }
+
+    #[test]
+    fn trusted_proxies_are_skipped_from_the_right() {
+        let proxies = TrustedProxies(vec![ip("10.0.0.1"), ip("10.0.0.2")]);
+
+        assert_eq!(
+            ip("203.0.113.9"),
+            proxies.client_ip(
+                ip("10.0.0.1"),
+                &[ip("198.51.100.1"), ip("203.0.113.9"), ip("10.0.0.2")]
+            )
+        );
+    }
+
+    #[test]
+    fn a_trusted_chain_falls_back_to_the_first_hop() {
+        let proxies = TrustedProxies(vec![ip("10.0.0.1"), ip("10.0.0.2")]);
+
+        assert_eq!(
+            ip("10.0.0.2"),
+            proxies.client_ip(ip("10.0.0.1"), &[ip("10.0.0.2")])
+        );
+        assert_eq!(ip("10.0.0.1"), proxies.client_ip(ip("10.0.0.1"), &[]));
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::{
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web::Data,
+    Error, HttpResponse,
+};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use futures_util::FutureExt;
+
+use super::client_ip::client_ip;
+use crate::settings::{BucketSettings, RateLimitSettings};
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+#[derive(Default)]
+struct State {
+    buckets: HashMap<String, Bucket>,
+    pruned: Option<Instant>,
+}
+
+/// In-memory token buckets keyed by client, each refilling one token every `refill`.
+struct Buckets {
+    capacity: f64,
+    refill: Duration,
+    state: Mutex<State>,
+}
+
+impl Buckets {
+    fn new(settings: &BucketSettings) -> Self {
+        Self {
+            capacity: f64::from(settings.capacity),
+            refill: Duration::from_secs(settings.refill_secs),
+            state: Mutex::new(State::default()),
+        }
+    }
+
+    fn refilled(&self, bucket: &Bucket, now: Instant) -> f64 {
+        if self.refill.is_zero() {
+            return self.capacity;
+        }
+
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        (bucket.tokens + elapsed / self.refill.as_secs_f64()).min(self.capacity)
+    }
+
+    fn take_at(&self, key: &str, now: Instant) -> Result<(), Duration> {
+        let mut state = self.state.lock().expect("Rate limit buckets are poisoned.");
+
+        // Full buckets behave like missing ones, so they are forgotten once every `refill` rather
+        // than on every request.
+        let prune = state
+            .pruned
+            .is_none_or(|pruned| now.saturating_duration_since(pruned) >= self.refill);
+        if prune {
+            state
+                .buckets
+                .retain(|_, bucket| self.refilled(bucket, now) < self.capacity);
+            state.pruned = Some(now);
+        }
+        let buckets = &mut state.buckets;
+
+        let tokens = buckets
+            .get(key)
+            .map_or(self.capacity, |bucket| self.refilled(bucket, now));
+
+        if tokens < 1.0 {
+            return Err(self.refill.mul_f64(1.0 - tokens));
+        }
+
+        buckets.insert(
+            key.to_owned(),
+            Bucket {
+                tokens: tokens - 1.0,
+                updated: now,
+            },
+        );
+
+        Ok(())
+    }
+}
+
+pub struct RateLimiter {
+    per_ip: Option<Buckets>,
+    per_email: Option<Buckets>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: Option<&RateLimitSettings>) -> Self {
+        Self {
+            per_ip: settings.and_then(|settings| settings.per_ip.as_ref().map(Buckets::new)),
+            per_email: settings.and_then(|settings| settings.per_email.as_ref().map(Buckets::new)),
+        }
+    }
+
+    pub fn check_ip(&self, ip: &str) -> Result<(), Duration> {
+        match &self.per_ip {
+            Some(buckets) => buckets.take_at(ip, Instant::now()),
+            None => Ok(()),
+        }
+    }
+
+    pub fn check_email(&self, email: &str) -> Result<(), Duration> {
+        match &self.per_email {
+            Some(buckets) => buckets.take_at(&email.to_lowercase(), Instant::now()),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn too_many_requests(retry_after: Duration) -> HttpResponse {
+    let secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+
+    HttpResponse::TooManyRequests()
+        .insert_header((header::RETRY_AFTER, secs.max(1).to_string()))
+        .finish()
+}
+
+/// Applies the per ip limit of the app's `RateLimiter` to the wrapped resource.
+pub struct RateLimit;
+
+impl<S> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware { service }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse, Error = Error>,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let limited = match (
+            req.app_data::<Data<RateLimiter>>(),
+            client_ip(req.peer_addr(), req.headers(), req.app_data()),
+        ) {
+            (Some(limiter), Some(ip)) => limiter.check_ip(&ip.to_string()).err(),
+            _ => None,
+        };
+
+        match limited {
+            Some(retry_after) => {
+                tracing::warn!("Rate limited submission from client ip.");
+                let response = req.into_response(too_many_requests(retry_after));
+                ready(Ok(response)).boxed_local()
+            }
+            None => self.service.call(req).boxed_local(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buckets(capacity: u32, refill_secs: u64) -> Buckets {
+        Buckets::new(&BucketSettings {
+            capacity,
+            refill_secs,
+        })
+    }
+
+    #[test]
+    fn buckets_allow_up_to_capacity() {
+        let buckets = buckets(2, 60);
+        let now = Instant::now();
+
+        assert_eq!(Ok(()), buckets.take_at("shaggy", now));
+        assert_eq!(Ok(()), buckets.take_at("shaggy", now));
+        assert_eq!(Err(Duration::from_secs(60)), buckets.take_at("shaggy", now));
+        assert_eq!(Ok(()), buckets.take_at("scooby", now));
+    }
+
+    #[test]
+    fn buckets_refill_over_time() {
+        let buckets = buckets(1, 60);
+        let now = Instant::now();
+
+        assert_eq!(Ok(()), buckets.take_at("shaggy", now));
+        assert_eq!(
+            Err(Duration::from_secs(30)),
+            buckets.take_at("shaggy", now + Duration::from_secs(30))
+        );
+        assert_eq!(
+            Ok(()),
+            buckets.take_at("shaggy", now + Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn full_buckets_are_forgotten() {
+        let buckets = buckets(1, 60);
+        let now = Instant::now();
+
+        buckets.take_at("shaggy", now).unwrap();
+        buckets
+            .take_at("scooby", now + Duration::from_secs(60))
+            .unwrap();
+
+        let keys: Vec<_> = buckets
+            .state
+            .lock()
+            .unwrap()
+            .buckets
+            .keys()
+            .cloned()
+            .collect();
+        assert_eq!(vec!["scooby".to_owned()], keys);
+    }
+
+    #[test]
+    fn full_buckets_are_only_forgotten_once_every_refill() {
+        let buckets = buckets(1, 60);
+        let now = Instant::now();
+
+        buckets.take_at("shaggy", now).unwrap();
+        buckets
+            .take_at("scooby", now + Duration::from_secs(30))
+            .unwrap();
+        buckets
+            .take_at("velma", now + Duration::from_secs(60))
+            .unwrap();
+        buckets
+            .take_at("fred", now + Duration::from_secs(90))
+            .unwrap();
+
+        let mut keys: Vec<_> = buckets
+            .state
+            .lock()
+            .unwrap()
+            .buckets
+            .keys()
+            .cloned()
+            .collect();
+        keys.sort();
+        assert_eq!(vec!["fred", "scooby", "velma"], keys);
+    }
+
+    #[test]
+    fn retry_after_rounds_up_to_whole_seconds() {
+        let response = too_many_requests(Duration::from_millis(1500));
+
+        assert_eq!("2", response.headers().get(header::RETRY_AFTER).unwrap());
+    }
+}
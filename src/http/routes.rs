@@ -1,10 +1,40 @@
+mod admin;
 mod contact;
 mod health_check;
+mod metrics;
+mod ready;
+mod token;
 
 use actix_web::web;
 
+use super::admin_auth::AdminAuth;
+use super::rate_limit::RateLimit;
+
 pub fn configure(config: &mut web::ServiceConfig) {
     config
-        .route("/", web::post().to(contact::handler))
-        .route("/health-check", web::get().to(health_check::handler));
+        .service(
+            web::resource("/")
+                .wrap(RateLimit)
+                .route(web::post().to(contact::handler)),
+        )
+        .service(
+            web::resource("/forms/{id}")
+                .wrap(RateLimit)
+                .route(web::post().to(contact::form_handler)),
+        )
+        .route("/token", web::get().to(token::handler))
+        .route("/health-check", web::get().to(health_check::handler))
+        .route("/ready", web::get().to(ready::handler))
+        .route("/metrics", web::get().to(metrics::handler));
+}
+
+/// Only registered when the admin api is enabled and submissions are stored.
+pub fn configure_admin(config: &mut web::ServiceConfig) {
+    config.service(
+        web::scope("/admin")
+            .wrap(AdminAuth)
+            .route("/submissions", web::get().to(admin::list))
+            .route("/submissions/{id}", web::get().to(admin::show))
+            .route("/submissions/{id}", web::delete().to(admin::delete)),
+    );
 }
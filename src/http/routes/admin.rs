@@ -0,0 +1,118 @@
+use actix_web::{
+    web::{Data, Path, Query},
+    HttpResponse,
+};
+use chrono::DateTime;
+
+use crate::outbox::Outbox;
+use crate::storage::{Filter, Storage, Submission};
+
+const DEFAULT_PER_PAGE: u32 = 50;
+const MAX_PER_PAGE: u32 = 100;
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ListQuery {
+    from: Option<String>,
+    to: Option<String>,
+    form: Option<String>,
+    email: Option<String>,
+    status: Option<String>,
+    spam: Option<bool>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+#[derive(serde::Serialize)]
+struct SubmissionPage {
+    submissions: Vec<Submission>,
+    page: u32,
+    per_page: u32,
+    total: i64,
+}
+
+fn timestamp(name: &str, value: Option<&str>) -> Result<Option<i64>, HttpResponse> {
+    value
+        .map(|value| {
+            DateTime::parse_from_rfc3339(value)
+                .map(|at| at.timestamp_millis())
+                .map_err(|_| {
+                    HttpResponse::BadRequest().json(serde_json::json!({
+                        name: "Must be an RFC 3339 timestamp."
+                    }))
+                })
+        })
+        .transpose()
+}
+
+fn storage_error(error: sqlx::Error) -> HttpResponse {
+    tracing::error!("Failed to read submissions: {:?}", error);
+    HttpResponse::InternalServerError().finish()
+}
+
+#[tracing::instrument(name = "List submissions.", skip(storage))]
+pub async fn list(
+    query: Query<ListQuery>,
+    storage: Data<Storage>,
+) -> Result<HttpResponse, HttpResponse> {
+    let query = query.into_inner();
+
+    let filter = Filter {
+        from: timestamp("from", query.from.as_deref())?,
+        to: timestamp("to", query.to.as_deref())?,
+        form_id: query.form,
+        email: query.email,
+        status: query.status,
+        spam: query.spam,
+    };
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query
+        .per_page
+        .unwrap_or(DEFAULT_PER_PAGE)
+        .clamp(1, MAX_PER_PAGE);
+
+    let (submissions, total) = storage
+        .list(
+            &filter,
+            i64::from(per_page),
+            i64::from(page - 1) * i64::from(per_page),
+        )
+        .await
+        .map_err(storage_error)?;
+
+    Ok(HttpResponse::Ok().json(SubmissionPage {
+        submissions,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+#[tracing::instrument(name = "Show submission.", skip(storage))]
+pub async fn show(id: Path<i64>, storage: Data<Storage>) -> Result<HttpResponse, HttpResponse> {
+    match storage.get(id.into_inner()).await.map_err(storage_error)? {
+        Some(submission) => Ok(HttpResponse::Ok().json(submission)),
+        None => Err(HttpResponse::NotFound().finish()),
+    }
+}
+
+/// Deletes the submission's outbox entries first, so a failure leaves it listed to try again.
+#[tracing::instrument(name = "Delete submission.", skip(storage, outbox))]
+pub async fn delete(
+    id: Path<i64>,
+    storage: Data<Storage>,
+    outbox: Data<Outbox>,
+) -> Result<HttpResponse, HttpResponse> {
+    let id = id.into_inner();
+
+    outbox.forget(id).await.map_err(|error| {
+        tracing::error!("Failed to delete outbox entries: {:?}", error);
+        HttpResponse::InternalServerError().finish()
+    })?;
+
+    if storage.delete(id).await.map_err(storage_error)? {
+        tracing::info!("Submission deleted.");
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Err(HttpResponse::NotFound().finish())
+    }
+}
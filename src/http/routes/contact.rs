@@ -1,68 +1,545 @@
-use std::convert::TryInto;
+mod messages;
+mod request;
+
+use std::collections::BTreeMap;
 
 use crate::{
-    domain::contact::{Contact, EmailError, MessageError, NameError},
+    captcha::{Captcha, CaptchaError},
+    domain::attachment::{AttachmentPolicy, Error as AttachmentError},
+    domain::contact::{
+        email::DomainPolicy, field, Contact, ContactPolicy, EmailError, Error as ContactError,
+        FieldError, MessageError, NameError,
+    },
+    domain::metadata::Metadata,
     email::EmailService,
+    http::{
+        client_ip::client_ip,
+        rate_limit::{too_many_requests, RateLimiter},
+    },
+    metrics::Metrics,
+    outbox::Outbox,
+    protection::BotProtection,
+    settings::{FieldSettings, FormSettings},
+    spam::{SpamFilter, Verdict},
+    storage::{Status as SubmissionStatus, Storage},
+};
+use actix_web::{
+    http::{header, StatusCode},
+    web::{Data, Path},
+    HttpRequest, HttpResponse,
 };
-use actix_web::{web::Data, web::Form, HttpResponse};
 
-#[derive(serde::Deserialize, Debug)]
-pub struct ContactRequest {
-    pub email: String,
-    pub name: String,
-    pub message: String,
+use messages::{Locale, Text};
+pub use request::ContactRequest;
+
+/// Holds message ids from the catalogs in `locales/` until localized for the response.
+#[derive(serde::Serialize, Debug, Default)]
+pub struct ContactErrors<M = Text> {
+    pub email: Option<M>,
+    pub name: Option<M>,
+    pub message: Option<M>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub captcha: Option<M>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spam: Option<M>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<M>,
+    /// Set when the body couldn't be read at all, leaving every other field unchecked.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<M>,
+    #[serde(flatten)]
+    pub fields: BTreeMap<String, M>,
+}
+
+impl ContactErrors {
+    fn localize(self, locale: Locale) -> ContactErrors<String> {
+        let message = |text| locale.message(text);
+
+        ContactErrors {
+            email: self.email.map(message),
+            name: self.name.map(message),
+            message: self.message.map(message),
+            captcha: self.captcha.map(message),
+            spam: self.spam.map(message),
+            attachments: self.attachments.map(message),
+            body: self.body.map(message),
+            fields: self
+                .fields
+                .into_iter()
+                .map(|(name, text)| (name, message(text)))
+                .collect(),
+        }
+    }
+
+    fn record(&self, metrics: Option<&Data<Metrics>>) {
+        let metrics = match metrics {
+            Some(metrics) => metrics,
+            None => return,
+        };
+
+        let errors = [
+            ("email", &self.email),
+            ("name", &self.name),
+            ("message", &self.message),
+            ("captcha", &self.captcha),
+            ("spam", &self.spam),
+            ("attachments", &self.attachments),
+            ("body", &self.body),
+        ];
+        for (field, text) in errors.iter() {
+            if let Some(text) = text {
+                metrics.validation_failed(field, text.id);
+            }
+        }
+        for (field, text) in &self.fields {
+            metrics.validation_failed(field, text.id);
+        }
+    }
+
+    fn response(self, locale: Locale) -> HttpResponse {
+        self.response_with_status(StatusCode::BAD_REQUEST, locale)
+    }
+
+    fn response_with_status(self, status: StatusCode, locale: Locale) -> HttpResponse {
+        HttpResponse::build(status)
+            .insert_header((header::CONTENT_LANGUAGE, locale.language()))
+            .json(self.localize(locale))
+    }
+}
+
+fn email_error(error: EmailError) -> Text {
+    match error {
+        EmailError::IsEmpty => "email-is-empty".into(),
+        EmailError::TooShort(min) => Text::with_length("email-too-short", min),
+        EmailError::TooLong(max) => Text::with_length("email-too-long", max),
+        EmailError::IsMissingAtSign => "email-missing-at-sign".into(),
+        EmailError::MultipleAtSigns => "email-multiple-at-signs".into(),
+        EmailError::InvalidLocalPart => "email-invalid-local-part".into(),
+        EmailError::InvalidDomain => "email-invalid-domain".into(),
+        EmailError::DomainNotAllowed => "email-domain-not-allowed".into(),
+    }
+}
+
+fn field_error(error: FieldError) -> Text {
+    match error {
+        FieldError::IsEmpty => "field-required".into(),
+        FieldError::TooShort(min) => Text::with_length("field-too-short", min),
+        FieldError::TooLong(max) => Text::with_length("field-too-long", max),
+        FieldError::TooSmall(min) => Text::with_limit("field-too-small", min),
+        FieldError::TooLarge(max) => Text::with_limit("field-too-large", max),
+        FieldError::InvalidEmail => "field-invalid-email".into(),
+        FieldError::InvalidPhone => "field-invalid-phone".into(),
+        FieldError::InvalidNumber => "field-invalid-number".into(),
+        FieldError::InvalidOption => "field-invalid-option".into(),
+        FieldError::InvalidBoolean => "field-invalid-boolean".into(),
+        FieldError::InvalidUrl => "field-invalid-url".into(),
+    }
+}
+
+fn attachment_error(error: AttachmentError) -> Text {
+    match error {
+        AttachmentError::NotAccepted => "attachments-not-accepted".into(),
+        AttachmentError::Empty => "attachments-empty".into(),
+        AttachmentError::TooMany(max) => Text::with_length("attachments-too-many", max),
+        AttachmentError::TooLarge(max) => Text::with_length("attachments-too-large", max),
+        AttachmentError::TotalTooLarge(max) => {
+            Text::with_length("attachments-total-too-large", max)
+        }
+        AttachmentError::TypeNotAllowed => "attachments-type-not-allowed".into(),
+    }
+}
+
+fn contact_errors(error: ContactError) -> ContactErrors {
+    ContactErrors {
+        email: error.email.map(email_error),
+        name: error.name.map(|e| match e {
+            NameError::IsEmpty => "name-is-empty".into(),
+            NameError::TooShort(min) => Text::with_length("name-too-short", min),
+            NameError::TooLong(max) => Text::with_length("name-too-long", max),
+        }),
+        message: error.message.map(|e| match e {
+            MessageError::IsEmpty => "message-is-empty".into(),
+            MessageError::TooShort(min) => Text::with_length("message-too-short", min),
+            MessageError::TooLong(max) => Text::with_length("message-too-long", max),
+        }),
+        ..ContactErrors::default()
+    }
+}
+
+impl ContactRequest {
+    #[allow(clippy::result_large_err)]
+    fn into_contact(
+        self,
+        policy: &ContactPolicy,
+        schema: &[FieldSettings],
+        attachment_policy: &AttachmentPolicy,
+    ) -> Result<Contact, ContactErrors> {
+        let fields = self
+            .fields
+            .into_iter()
+            .filter_map(|(name, value)| match value {
+                serde_json::Value::String(value) => Some((name, value)),
+                serde_json::Value::Null => None,
+                value => Some((name, value.to_string())),
+            })
+            .collect();
+
+        match (
+            Contact::new(&self.email, &self.name, &self.message, policy),
+            field::validate(schema, fields),
+            attachment_policy.validate(self.uploads),
+        ) {
+            (Ok(contact), Ok(fields), Ok(attachments)) => {
+                Ok(contact.with_fields(fields).with_attachments(attachments))
+            }
+            (contact, fields, attachments) => {
+                let mut errors = contact
+                    .err()
+                    .map_or_else(ContactErrors::default, contact_errors);
+                errors.fields = fields
+                    .err()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(name, error)| (name, field_error(error)))
+                    .collect();
+                errors.attachments = attachments.err().map(attachment_error);
+                Err(errors)
+            }
+        }
+    }
 }
 
-#[derive(serde::Serialize, Debug)]
-pub struct ContactErrors {
-    pub email: Option<&'static str>,
-    pub name: Option<&'static str>,
-    pub message: Option<&'static str>,
+/// Required fields beyond email, name and message, which are always required.
+fn missing_fields(form: &FormSettings, request: &ContactRequest) -> BTreeMap<String, Text> {
+    form.required_fields
+        .iter()
+        .filter(|name| !matches!(name.as_str(), "email" | "name" | "message"))
+        .filter(|name| match request.fields.get(name.as_str()) {
+            Some(serde_json::Value::String(value)) => value.trim().is_empty(),
+            Some(serde_json::Value::Null) | None => true,
+            Some(_) => false,
+        })
+        .map(|name| (name.to_owned(), "field-required".into()))
+        .collect()
 }
 
-impl TryInto<Contact> for ContactRequest {
-    type Error = ContactErrors;
-
-    fn try_into(self) -> Result<Contact, Self::Error> {
-        Contact::new(&self.email, &self.name, &self.message).map_err(|error| ContactErrors {
-            email: error.email.map(|e| match e {
-                EmailError::IsEmpty => "Email may not be empty.",
-                EmailError::IsMissingAtSign => "Email is missing @ symbol.",
-                EmailError::IsGreaterThan300 => "Email may not be longer than 200 characters long.",
-            }),
-            name: error.name.map(|e| match e {
-                NameError::IsEmpty => "Name may not be empty.",
-                NameError::IsGreaterThan200 => "Name may not be longer than 200 characters long.",
-            }),
-            message: error.message.map(|e| match e {
-                MessageError::IsEmpty => "Message may not be empty.",
-                MessageError::IsGreaterThan2000 => {
-                    "Message may not be longer than 2000 characters long."
-                }
-            }),
+fn metadata(http_request: &HttpRequest, form_id: Option<String>) -> Metadata {
+    let header_value = |name| {
+        http_request
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from)
+    };
+
+    Metadata {
+        submitted_at: chrono::Utc::now(),
+        form_id,
+        client_ip: client_ip(
+            http_request.peer_addr(),
+            http_request.headers(),
+            http_request.app_data(),
+        )
+        .map(|ip| ip.to_string()),
+        user_agent: header_value(header::USER_AGENT),
+        referer: header_value(header::REFERER),
+        spam_score: None,
+    }
+}
+
+/// Records the submission when storage is configured, returning its id.
+async fn store(
+    storage: Option<&Data<Storage>>,
+    contact: &Contact,
+    metadata: &Metadata,
+    verdict: Verdict,
+    status: SubmissionStatus,
+) -> Result<Option<i64>, HttpResponse> {
+    let storage = match storage {
+        Some(storage) => storage,
+        None => return Ok(None),
+    };
+
+    storage
+        .insert(contact, metadata, verdict, status)
+        .await
+        .map(Some)
+        .map_err(|error| {
+            tracing::error!("Failed to store contact: {:?}", error);
+            HttpResponse::InternalServerError().finish()
         })
+}
+
+/// What a queued submission gets, and so does a dropped bot submission, so it can't tell.
+fn accepted(form: Option<&FormSettings>) -> HttpResponse {
+    match form.and_then(|form| form.redirect_to.as_ref()) {
+        Some(location) => HttpResponse::SeeOther()
+            .insert_header((header::LOCATION, location.as_str()))
+            .finish(),
+        None => HttpResponse::Accepted().finish(),
+    }
+}
+
+/// Removes a stored submission that never made it into the outbox, as the visitor is told to
+/// try again and would otherwise leave a pending copy behind that is never delivered.
+async fn discard(storage: Option<&Data<Storage>>, id: Option<i64>) {
+    if let (Some(storage), Some(id)) = (storage, id) {
+        if let Err(error) = storage.delete(id).await {
+            tracing::error!("Unable to delete unqueued submission: {:?}", error);
+        }
     }
 }
 
-#[tracing::instrument(name = "Contact handler.", skip(email_service))]
+/// What submissions are validated and screened against, extracted together as handlers take at
+/// most twelve arguments.
+type Policies = (
+    Data<ContactPolicy>,
+    Data<AttachmentPolicy>,
+    Data<DomainPolicy>,
+    Data<SpamFilter>,
+);
+
+#[tracing::instrument(
+    name = "Contact handler.",
+    skip(
+        http_request,
+        protection,
+        captcha,
+        rate_limiter,
+        email_service,
+        outbox,
+        policies
+    )
+)]
+#[allow(clippy::too_many_arguments)]
 pub async fn handler(
-    request: Form<ContactRequest>,
+    http_request: HttpRequest,
+    request: ContactRequest,
+    protection: Data<BotProtection>,
+    captcha: Data<Captcha>,
+    rate_limiter: Data<RateLimiter>,
+    email_service: Data<EmailService>,
+    outbox: Data<Outbox>,
+    policies: Policies,
+) -> Result<HttpResponse, HttpResponse> {
+    submit(
+        &http_request,
+        request,
+        None,
+        &protection,
+        &captcha,
+        &rate_limiter,
+        &email_service,
+        &outbox,
+        &policies,
+    )
+    .await
+}
+
+#[tracing::instrument(
+    name = "Form handler.",
+    skip(
+        http_request,
+        forms,
+        protection,
+        captcha,
+        rate_limiter,
+        email_service,
+        outbox,
+        policies
+    )
+)]
+#[allow(clippy::too_many_arguments)]
+pub async fn form_handler(
+    http_request: HttpRequest,
+    form_id: Path<String>,
+    request: ContactRequest,
+    forms: Data<BTreeMap<String, FormSettings>>,
+    protection: Data<BotProtection>,
+    captcha: Data<Captcha>,
+    rate_limiter: Data<RateLimiter>,
     email_service: Data<EmailService>,
+    outbox: Data<Outbox>,
+    policies: Policies,
 ) -> Result<HttpResponse, HttpResponse> {
+    let form_id = form_id.into_inner();
+
+    let form = forms.get(&form_id).ok_or_else(|| {
+        tracing::info!("Unknown form.");
+        HttpResponse::NotFound().finish()
+    })?;
+
+    submit(
+        &http_request,
+        request,
+        Some((form_id, form)),
+        &protection,
+        &captcha,
+        &rate_limiter,
+        &email_service,
+        &outbox,
+        &policies,
+    )
+    .await
+}
+
+#[allow(clippy::too_many_arguments, clippy::result_large_err)]
+async fn submit(
+    http_request: &HttpRequest,
+    mut request: ContactRequest,
+    form: Option<(String, &FormSettings)>,
+    protection: &BotProtection,
+    captcha: &Captcha,
+    rate_limiter: &RateLimiter,
+    email_service: &EmailService,
+    outbox: &Outbox,
+    (policy, attachment_policy, domain_policy, spam_filter): &Policies,
+) -> Result<HttpResponse, HttpResponse> {
+    let (form_id, form) = match form {
+        Some((form_id, form)) => (Some(form_id), Some(form)),
+        None => (None, None),
+    };
+
+    let mut metadata = metadata(http_request, form_id);
+    let metrics = http_request.app_data::<Data<Metrics>>();
+
+    if let Err(error) = protection.check(&mut request.fields) {
+        tracing::warn!("Dropping suspected bot submission: {:?}", error);
+        return Ok(accepted(form));
+    }
+
+    let lang = request.fields.remove("lang");
+    let locale = Locale::new(
+        lang.as_ref().and_then(serde_json::Value::as_str),
+        http_request
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok()),
+    );
+
+    captcha
+        .verify(&mut request.fields, metadata.client_ip.as_deref())
+        .await
+        .map_err(|error| {
+            tracing::info!("Failed to verify captcha: {:?}", error);
+            let errors = ContactErrors {
+                captcha: Some(
+                    match error {
+                        CaptchaError::Missing => "captcha-missing",
+                        CaptchaError::Rejected => "captcha-rejected",
+                        CaptchaError::Unavailable => "captcha-unavailable",
+                    }
+                    .into(),
+                ),
+                ..ContactErrors::default()
+            };
+            errors.record(metrics);
+            errors.response(locale)
+        })?;
+
     tracing::info!("Attempting to parse contact request.");
 
-    let contact = request.0.try_into().map_err(|errors| {
+    let missing = form
+        .map(|form| missing_fields(form, &request))
+        .unwrap_or_default();
+    let schema = form.map_or(&[][..], |form| &form.fields);
+
+    let contact: Result<Contact, ContactErrors> = request
+        .into_contact(policy, schema, attachment_policy)
+        .and_then(|contact| {
+            domain_policy
+                .check(&contact.email)
+                .map(|_| contact)
+                .map_err(|error| ContactErrors {
+                    email: Some(email_error(error)),
+                    ..ContactErrors::default()
+                })
+        });
+    let contact = match (contact, missing.is_empty()) {
+        (Ok(contact), true) => Ok(contact),
+        (Ok(_), false) => Err(ContactErrors {
+            fields: missing,
+            ..ContactErrors::default()
+        }),
+        (Err(mut errors), _) => {
+            errors.fields.extend(missing);
+            Err(errors)
+        }
+    }
+    .map_err(|errors| {
         tracing::info!("Failed to parse contact request: {:?}", errors);
-        HttpResponse::BadRequest().json(errors)
+        errors.record(metrics);
+        errors.response(locale)
     })?;
 
     tracing::info!("Successfully parsed contact request: {:?}", contact);
 
-    email_service.send(contact).await.map_err(|error| {
-        tracing::error!("Failed to process contact: {:?}", error);
-        HttpResponse::InternalServerError().finish()
-    })?;
+    rate_limiter
+        .check_email(contact.email.as_ref())
+        .map_err(|retry_after| {
+            tracing::warn!("Rate limited submission from email address.");
+            too_many_requests(retry_after)
+        })?;
+
+    let verdict = spam_filter
+        .verdict(&contact)
+        .max(email_service.classify(&contact, &metadata).await);
+    if let Some(metrics) = metrics {
+        metrics.spam_verdict(verdict);
+    }
+
+    let storage = http_request.app_data::<Data<Storage>>();
+
+    match verdict {
+        Verdict::Deliver => {}
+        Verdict::Tag(score) | Verdict::Quarantine(score) => {
+            tracing::info!("Contact scored {} as spam.", score);
+            metadata.spam_score = Some(score);
+        }
+        Verdict::Reject(score) => {
+            tracing::info!("Contact scored {} as spam, rejecting.", score);
+            store(
+                storage,
+                &contact,
+                &metadata,
+                verdict,
+                SubmissionStatus::Rejected,
+            )
+            .await?;
+            return Err(ContactErrors {
+                spam: Some("spam-rejected".into()),
+                ..ContactErrors::default()
+            }
+            .response(locale));
+        }
+    }
+
+    email_service
+        .backup(&contact, &metadata)
+        .await
+        .map_err(|error| {
+            tracing::error!("Failed to back up contact: {:?}", error);
+            HttpResponse::InternalServerError().finish()
+        })?;
+
+    let status = match verdict {
+        Verdict::Quarantine(_) => SubmissionStatus::Quarantined,
+        _ => SubmissionStatus::Pending,
+    };
+    let id = store(storage, &contact, &metadata, verdict, status).await?;
+
+    let queued = match verdict {
+        Verdict::Quarantine(_) => outbox.quarantine(&contact, &metadata, id).await,
+        _ => outbox.enqueue(&contact, &metadata, id).await,
+    };
+
+    if let Err(error) = queued {
+        tracing::error!("Failed to queue contact: {:?}", error);
+        discard(storage, id).await;
+        return Err(HttpResponse::InternalServerError().finish());
+    }
+
+    tracing::info!("Successfully queued contact");
 
-    tracing::info!("Successfully processed contact");
-    Ok(HttpResponse::NoContent().finish())
+    Ok(accepted(form))
 }
@@ -0,0 +1,209 @@
+use std::convert::TryFrom;
+use std::sync::LazyLock;
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use unic_langid::LanguageIdentifier;
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// English comes first and is the fallback for unsupported languages and missing messages.
+const CATALOGS: &[(&str, &str)] = &[
+    ("en", include_str!("../../../../locales/en.ftl")),
+    ("es", include_str!("../../../../locales/es.ftl")),
+    ("fr", include_str!("../../../../locales/fr.ftl")),
+];
+
+static BUNDLES: LazyLock<Vec<Bundle>> = LazyLock::new(|| {
+    CATALOGS
+        .iter()
+        .map(|(language, source)| bundle(language, source))
+        .collect()
+});
+
+fn bundle(language: &str, source: &str) -> Bundle {
+    let language: LanguageIdentifier = language
+        .parse()
+        .expect("Unable to parse message catalog language.");
+    let resource =
+        FluentResource::try_new(source.to_owned()).expect("Unable to parse message catalog.");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("Unable to add message catalog.");
+    bundle
+}
+
+fn format(bundle: &Bundle, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let message = bundle.format_pattern(pattern, args, &mut errors);
+
+    if !errors.is_empty() {
+        tracing::warn!("Unable to format message {}: {:?}", id, errors);
+    }
+
+    Some(message.into_owned())
+}
+
+/// A catalog message id, with the limit that was exceeded for length errors.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Text {
+    pub id: &'static str,
+    pub limit: Option<i64>,
+}
+
+impl Text {
+    pub fn with_limit(id: &'static str, limit: i64) -> Self {
+        Self {
+            id,
+            limit: Some(limit),
+        }
+    }
+
+    pub fn with_length(id: &'static str, length: usize) -> Self {
+        Self::with_limit(id, i64::try_from(length).unwrap_or(i64::MAX))
+    }
+}
+
+impl From<&'static str> for Text {
+    fn from(id: &'static str) -> Self {
+        Self { id, limit: None }
+    }
+}
+
+/// Language tags from an `Accept-Language` header, most preferred first.
+fn accept_language(header: &str) -> Vec<&str> {
+    let mut tags: Vec<(&str, f32)> = header
+        .split(',')
+        .filter_map(|range| {
+            let mut parts = range.split(';').map(str::trim);
+            let tag = parts.next().filter(|tag| !tag.is_empty())?;
+            let quality = parts
+                .find_map(|part| part.strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse().ok())?;
+
+            Some((tag, quality))
+        })
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+
+    // Stable, so tags of equal quality keep the order they were sent in.
+    tags.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+    tags.into_iter().map(|(tag, _)| tag).collect()
+}
+
+/// The catalog language errors are reported in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Locale(usize);
+
+impl Locale {
+    /// Picks the first supported language, matching on the primary subtag so `fr-CA` gets `fr`.
+    fn negotiate<'a>(requested: impl IntoIterator<Item = &'a str>) -> Self {
+        requested
+            .into_iter()
+            .find_map(|tag| {
+                let language = tag.split(['-', '_']).next()?.to_lowercase();
+                CATALOGS
+                    .iter()
+                    .position(|(catalog, _)| *catalog == language)
+            })
+            .map_or_else(Self::default, Self)
+    }
+
+    /// A `lang` field on the submission takes precedence over the `Accept-Language` header.
+    pub fn new(lang: Option<&str>, header: Option<&str>) -> Self {
+        let requested = lang
+            .into_iter()
+            .chain(header.map(accept_language).unwrap_or_default());
+
+        Self::negotiate(requested)
+    }
+
+    pub fn language(&self) -> &'static str {
+        CATALOGS[self.0].0
+    }
+
+    pub fn message(&self, text: Text) -> String {
+        let args = text.limit.map(|limit| {
+            let mut args = FluentArgs::new();
+            args.set("limit", limit);
+            args
+        });
+
+        format(&BUNDLES[self.0], text.id, args.as_ref())
+            .or_else(|| format(&BUNDLES[0], text.id, args.as_ref()))
+            .unwrap_or_else(|| {
+                tracing::warn!("Missing message {}.", text.id);
+                text.id.to_owned()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(source: &str) -> Vec<&str> {
+        source
+            .lines()
+            .filter(|line| !line.starts_with([' ', '#']))
+            .filter_map(|line| line.split_once(" = "))
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    #[test]
+    fn accept_language_is_ordered_by_quality() {
+        assert_eq!(
+            vec!["fr-CA", "es", "en"],
+            accept_language("en;q=0.5, fr-CA, es;q=0.8, de;q=0")
+        );
+        assert_eq!(Vec::<&str>::new(), accept_language(""));
+    }
+
+    #[test]
+    fn negotiates_the_first_supported_language() {
+        assert_eq!(
+            "fr",
+            Locale::new(None, Some("de, fr-CA;q=0.9, es;q=0.8")).language()
+        );
+        assert_eq!("es", Locale::new(None, Some("es_MX")).language());
+        assert_eq!("en", Locale::new(None, Some("de, ja")).language());
+        assert_eq!("en", Locale::new(None, None).language());
+    }
+
+    #[test]
+    fn lang_field_takes_precedence_over_the_header() {
+        assert_eq!("es", Locale::new(Some("es"), Some("fr")).language());
+        assert_eq!("fr", Locale::new(Some("de"), Some("fr")).language());
+    }
+
+    #[test]
+    fn formats_messages_in_the_locale() {
+        let locale = Locale::new(Some("fr"), None);
+
+        assert_eq!(
+            "Le nom ne peut pas être vide.",
+            locale.message("name-is-empty".into())
+        );
+        assert_eq!(
+            "Le message ne peut pas dépasser 10000 caractères.",
+            locale.message(Text::with_limit("message-too-long", 10_000))
+        );
+        assert_eq!("unknown-message", locale.message("unknown-message".into()));
+    }
+
+    #[test]
+    fn every_catalog_has_every_english_message() {
+        let english = ids(CATALOGS[0].1);
+
+        for (language, source) in CATALOGS {
+            let translated = ids(source);
+            for id in &english {
+                assert!(translated.contains(id), "{} is missing {}", language, id);
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::BTreeMap;
+
+use actix_multipart::{Field, Multipart, MultipartError};
+use actix_web::{
+    dev::Payload,
+    error::{ErrorBadRequest, ErrorPayloadTooLarge, ErrorUnsupportedMediaType, InternalError},
+    http::{header, StatusCode},
+    web, Error, FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::{future::LocalBoxFuture, FutureExt, StreamExt};
+
+use super::messages::Locale;
+use super::{attachment_error, ContactErrors};
+use crate::domain::attachment::{AttachmentPolicy, Error as AttachmentError};
+use crate::metrics::Metrics;
+
+/// Largest text field accepted, matching the default limit of `web::Form`.
+const FIELD_LIMIT: usize = 16_384;
+/// Most fields accepted beyond email, name and message, those of form schemas included.
+const MAX_FIELDS: usize = 64;
+/// Longest field name accepted, in bytes.
+const MAX_FIELD_NAME: usize = 256;
+
+#[derive(serde::Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ContactRequest {
+    pub email: String,
+    pub name: String,
+    pub message: String,
+    #[serde(flatten)]
+    pub fields: BTreeMap<String, serde_json::Value>,
+    /// Filenames and contents of file parts, only ever sent in multipart bodies.
+    #[serde(skip)]
+    pub uploads: Vec<(String, Vec<u8>)>,
+}
+
+/// Extracts a `ContactRequest` from a json, urlencoded or multipart body.
+///
+/// Fields missing from a body are treated as empty, so the visitor gets the usual field errors
+/// back. Bodies that can't be read at all are rejected with 413 when too large, 415 when of an
+/// unsupported type and 400 otherwise.
+impl FromRequest for ContactRequest {
+    type Config = ();
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let rejection = Rejection::new(req);
+
+        let mime = match req.mime_type() {
+            Ok(Some(mime)) => mime,
+            _ => {
+                let error = rejection.reject(ErrorUnsupportedMediaType("Missing content type."));
+                return async { Err(error) }.boxed_local();
+            }
+        };
+
+        match (mime.type_(), mime.subtype()) {
+            (mime::APPLICATION, mime::JSON) => web::Json::<Self>::from_request(req, payload)
+                .map(move |result| {
+                    result
+                        .and_then(|json| limit_fields(json.into_inner()))
+                        .map_err(|error| rejection.reject(error))
+                })
+                .boxed_local(),
+            (mime::APPLICATION, mime::WWW_FORM_URLENCODED) => {
+                web::Form::<Self>::from_request(req, payload)
+                    .map(move |result| {
+                        result
+                            .and_then(|form| limit_fields(form.into_inner()))
+                            .map_err(|error| rejection.reject(error))
+                    })
+                    .boxed_local()
+            }
+            (mime::MULTIPART, mime::FORM_DATA) => {
+                let multipart = Multipart::new(req.headers(), payload.take());
+                let policy = req
+                    .app_data::<web::Data<AttachmentPolicy>>()
+                    .cloned()
+                    .unwrap_or_else(|| web::Data::new(AttachmentPolicy::new(None)));
+                async move {
+                    from_multipart(multipart, &policy)
+                        .await
+                        .and_then(|request| Ok(limit_fields(request)?))
+                        .map_err(|error| rejection.reject(error))
+                }
+                .boxed_local()
+            }
+            _ => {
+                let error =
+                    rejection.reject(ErrorUnsupportedMediaType("Unsupported content type."));
+                async { Err(error) }.boxed_local()
+            }
+        }
+    }
+}
+
+/// Why a body was rejected before any of its fields were checked.
+enum Unreadable {
+    Body(Error),
+    /// The file parts alone broke the attachment limits, so the rest of the body wasn't read.
+    Attachments(AttachmentError),
+}
+
+impl From<Error> for Unreadable {
+    fn from(error: Error) -> Self {
+        Self::Body(error)
+    }
+}
+
+impl From<MultipartError> for Unreadable {
+    fn from(error: MultipartError) -> Self {
+        Self::Body(error.into())
+    }
+}
+
+/// Answers unreadable bodies like field errors, localized by `Accept-Language` as the body's own
+/// `lang` field can't be read.
+struct Rejection {
+    locale: Locale,
+    metrics: Option<web::Data<Metrics>>,
+}
+
+impl Rejection {
+    fn new(req: &HttpRequest) -> Self {
+        Self {
+            locale: Locale::new(
+                None,
+                req.headers()
+                    .get(header::ACCEPT_LANGUAGE)
+                    .and_then(|value| value.to_str().ok()),
+            ),
+            metrics: req.app_data::<web::Data<Metrics>>().cloned(),
+        }
+    }
+
+    fn reject(&self, unreadable: impl Into<Unreadable>) -> Error {
+        let (status, errors) = match unreadable.into() {
+            Unreadable::Body(error) => {
+                tracing::info!("Unable to read contact request body: {}", error);
+
+                let status = error.as_response_error().status_code();
+                let text = match status {
+                    StatusCode::PAYLOAD_TOO_LARGE => "body-too-large",
+                    StatusCode::UNSUPPORTED_MEDIA_TYPE => "body-unsupported-type",
+                    _ => "body-malformed",
+                };
+                let status = match status {
+                    StatusCode::PAYLOAD_TOO_LARGE | StatusCode::UNSUPPORTED_MEDIA_TYPE => status,
+                    _ => StatusCode::BAD_REQUEST,
+                };
+
+                let errors = ContactErrors {
+                    body: Some(text.into()),
+                    ..ContactErrors::default()
+                };
+                (status, errors)
+            }
+            Unreadable::Attachments(error) => {
+                tracing::info!("Stopped reading contact request attachments: {:?}", error);
+
+                let errors = ContactErrors {
+                    attachments: Some(attachment_error(error)),
+                    ..ContactErrors::default()
+                };
+                (StatusCode::BAD_REQUEST, errors)
+            }
+        };
+        errors.record(self.metrics.as_ref());
+
+        let response = errors.response_with_status(status, self.locale);
+        InternalError::from_response("Unable to read contact request body.", response).into()
+    }
+}
+
+/// Rejects requests carrying more, or larger, extra fields than any form would need, as they
+/// end up in every notification.
+fn limit_fields(request: ContactRequest) -> Result<ContactRequest, Error> {
+    if request.fields.len() > MAX_FIELDS {
+        return Err(ErrorBadRequest("Too many fields."));
+    }
+
+    if request
+        .fields
+        .keys()
+        .any(|name| name.len() > MAX_FIELD_NAME)
+    {
+        return Err(ErrorBadRequest("Field name is too long."));
+    }
+
+    let size = |value: &serde_json::Value| match value {
+        serde_json::Value::String(value) => value.len(),
+        value => value.to_string().len(),
+    };
+    if request
+        .fields
+        .values()
+        .any(|value| size(value) > FIELD_LIMIT)
+    {
+        return Err(ErrorPayloadTooLarge("Field is too large."));
+    }
+
+    Ok(request)
+}
+
+/// Reads a file part, keeping at most `limit` bytes of it. Every byte counts towards `total`,
+/// so reading stops as soon as the file parts together break the policy.
+async fn read_upload(
+    field: &mut Field,
+    limit: usize,
+    total: &mut usize,
+    policy: &AttachmentPolicy,
+) -> Result<Vec<u8>, Unreadable> {
+    let mut content = Vec::new();
+
+    while let Some(chunk) = field.next().await {
+        let chunk = chunk?;
+        *total += chunk.len();
+        policy.check_read(*total).map_err(Unreadable::Attachments)?;
+
+        let take = chunk.len().min(limit - content.len());
+        content.extend_from_slice(&chunk[..take]);
+    }
+
+    Ok(content)
+}
+
+async fn from_multipart(
+    mut multipart: Multipart,
+    policy: &AttachmentPolicy,
+) -> Result<ContactRequest, Unreadable> {
+    let (max_files, max_file_bytes) = policy.read_limits();
+    let mut fields = serde_json::Map::new();
+    let mut uploads = Vec::new();
+    let mut total = 0;
+
+    while let Some(field) = multipart.next().await {
+        let mut field = field?;
+
+        let disposition = field.content_disposition();
+        let name = disposition
+            .as_ref()
+            .and_then(|disposition| disposition.get_name().map(String::from));
+        let filename = disposition
+            .as_ref()
+            .and_then(|disposition| disposition.get_filename().map(String::from));
+
+        // Browsers send an empty file part for file inputs left empty.
+        if let Some(filename) = filename {
+            let limit = if uploads.len() < max_files {
+                max_file_bytes
+            } else {
+                0
+            };
+            let content = read_upload(&mut field, limit, &mut total, policy).await?;
+
+            if !filename.is_empty() && uploads.len() < max_files {
+                uploads.push((filename, content));
+            }
+            continue;
+        }
+
+        let mut value = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk?;
+            if value.len() + chunk.len() > FIELD_LIMIT {
+                return Err(ErrorPayloadTooLarge("Multipart field is too large.").into());
+            }
+            value.extend_from_slice(&chunk);
+        }
+
+        if let Some(name) = name {
+            let value = String::from_utf8(value).map_err(ErrorBadRequest)?;
+            fields.insert(name, serde_json::Value::String(value));
+        }
+    }
+
+    let request: ContactRequest =
+        serde_json::from_value(serde_json::Value::Object(fields)).map_err(ErrorBadRequest)?;
+
+    Ok(ContactRequest { uploads, ..request })
+}
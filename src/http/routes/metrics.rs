@@ -0,0 +1,15 @@
+use actix_web::{web::Data, HttpResponse};
+
+use crate::metrics::{Metrics, CONTENT_TYPE};
+use crate::outbox::Outbox;
+
+pub async fn handler(metrics: Data<Metrics>, outbox: Data<Outbox>) -> HttpResponse {
+    match outbox.depth().await {
+        Ok(depth) => metrics.set_queue_depth(depth),
+        Err(error) => tracing::warn!("Unable to count pending outbox entries: {:?}", error),
+    }
+
+    HttpResponse::Ok()
+        .content_type(CONTENT_TYPE)
+        .body(metrics.render())
+}
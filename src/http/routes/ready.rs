@@ -0,0 +1,31 @@
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+
+use crate::email::EmailService;
+use crate::outbox::Outbox;
+use crate::readiness::Readiness;
+use crate::storage::Storage;
+
+/// Unlike `/health-check`, fails while submissions couldn't be backed up or delivered.
+pub async fn handler(
+    http_request: HttpRequest,
+    readiness: Data<Readiness>,
+    email_service: Data<EmailService>,
+    outbox: Data<Outbox>,
+) -> HttpResponse {
+    let storage = http_request.app_data::<Data<Storage>>();
+    let report = readiness
+        .report(
+            &email_service,
+            &outbox,
+            storage.map(|storage| storage.get_ref()),
+        )
+        .await;
+
+    let mut response = if report.is_ready() {
+        HttpResponse::Ok()
+    } else {
+        HttpResponse::ServiceUnavailable()
+    };
+
+    response.json(report)
+}
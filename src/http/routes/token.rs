@@ -0,0 +1,15 @@
+use actix_web::{web::Data, HttpResponse};
+
+use crate::protection::BotProtection;
+
+#[derive(serde::Serialize)]
+struct TokenResponse {
+    token: String,
+}
+
+pub async fn handler(protection: Data<BotProtection>) -> HttpResponse {
+    match protection.issue_token() {
+        Some(token) => HttpResponse::Ok().json(TokenResponse { token }),
+        None => HttpResponse::NotFound().finish(),
+    }
+}
@@ -1,18 +1,182 @@
+mod captcha;
+mod chat;
 mod domain;
 mod email;
+#[cfg(test)]
+mod fixtures;
 mod http;
 pub mod logging;
+mod metrics;
+mod notifier;
+mod outbox;
+mod protection;
+mod readiness;
 pub mod settings;
+mod spam;
+mod storage;
+mod webhook;
+
+use std::sync::Arc;
 
 pub use http::HttpApp;
 
-use settings::{EmailSettings, HttpSettings};
+use domain::attachment::AttachmentPolicy;
+use domain::contact::{ContactPolicy, LengthPolicy};
+use notifier::{Notifier, Notifiers};
+use settings::{
+    ChatSettings, LengthSettings, NotifierKind, NotifyPolicy, NotifySettings, Settings,
+    ValidationSettings,
+};
+
+fn contact_policy(settings: Option<ValidationSettings>) -> ContactPolicy {
+    let defaults = ContactPolicy::default();
+    let settings = match settings {
+        Some(settings) => settings,
+        None => return defaults,
+    };
+    let length = |length: Option<LengthSettings>, default| {
+        length.map_or(default, |length| LengthPolicy {
+            min: length.min,
+            max: length.max,
+        })
+    };
+
+    ContactPolicy {
+        email: length(settings.email, defaults.email),
+        name: length(settings.name, defaults.name),
+        message: length(settings.message, defaults.message),
+    }
+}
+
+/// Email and every configured webhook and chat, unless the notifiers are listed in settings.
+fn notifiers(
+    settings: Option<NotifySettings>,
+    email_service: &Arc<email::EmailService>,
+    webhooks: Vec<webhook::Webhook>,
+    chat: Option<ChatSettings>,
+) -> Notifiers {
+    let chat = chat.unwrap_or_default();
+
+    let mut available: Vec<(NotifierKind, Box<dyn Notifier>)> =
+        vec![(NotifierKind::Email, Box::new(email_service.clone()))];
+    for webhook in webhooks {
+        available.push((NotifierKind::Webhooks, Box::new(webhook)));
+    }
+    if let Some(settings) = chat.slack {
+        available.push((NotifierKind::Slack, Box::new(chat::Slack::new(settings))));
+    }
+    if let Some(settings) = chat.discord {
+        available.push((
+            NotifierKind::Discord,
+            Box::new(chat::Discord::new(settings)),
+        ));
+    }
+    if let Some(settings) = chat.mattermost {
+        available.push((
+            NotifierKind::Mattermost,
+            Box::new(chat::Mattermost::new(settings)),
+        ));
+    }
+    if let Some(settings) = chat.matrix {
+        available.push((NotifierKind::Matrix, Box::new(chat::Matrix::new(settings))));
+    }
+
+    let (policy, kinds) = match settings {
+        Some(settings) => (settings.policy, settings.notifiers),
+        None => (NotifyPolicy::default(), None),
+    };
+    let kinds = match kinds {
+        Some(kinds) => kinds,
+        None => {
+            return Notifiers::new(
+                available
+                    .into_iter()
+                    .map(|(_, notifier)| notifier)
+                    .collect(),
+                policy,
+            )
+        }
+    };
+
+    let mut notifiers = Vec::new();
+    for kind in &kinds {
+        let (chosen, rest): (Vec<_>, Vec<_>) = available
+            .into_iter()
+            .partition(|(available, _)| available == kind);
+        available = rest;
+
+        if chosen.is_empty() {
+            tracing::warn!("Notifier {:?} is not configured, skipping it.", kind);
+        }
+        notifiers.extend(chosen.into_iter().map(|(_, notifier)| notifier));
+    }
+
+    Notifiers::new(notifiers, policy)
+}
+
+pub async fn start(settings: Settings) -> std::io::Result<HttpApp> {
+    let metrics = metrics::Metrics::new();
+    let email_service = Arc::new(
+        email::EmailService::new(settings.email, &settings.forms).with_metrics(metrics.clone()),
+    );
+
+    let outbox = outbox::Outbox::connect(&settings.outbox)
+        .await
+        .map_err(std::io::Error::other)?;
+
+    let storage = match &settings.storage {
+        Some(settings) => Some(
+            storage::Storage::connect(settings)
+                .await
+                .map_err(std::io::Error::other)?,
+        ),
+        None => None,
+    };
+
+    let outbox = outbox.with_storage(storage.clone());
+    let notifiers = notifiers(
+        settings.notify,
+        &email_service,
+        webhook::Webhook::all(&settings.webhooks).map_err(std::io::Error::other)?,
+        settings.chat,
+    );
+    actix_rt::spawn(outbox.clone().run(notifiers, email_service.clone()));
 
-pub fn start(
-    http_settings: HttpSettings,
-    email_settings: EmailSettings,
-) -> std::io::Result<HttpApp> {
-    let email_service = email::EmailService::new(email_settings);
+    let protection = protection::BotProtection::new(settings.bot_protection);
+    let captcha = captcha::Captcha::new(settings.captcha);
+    let spam_filter = spam::SpamFilter::new(settings.spam.as_ref());
+    let contact_policy = contact_policy(settings.validation);
+    let domain_policy = settings
+        .email_domains
+        .map(|settings| {
+            domain::contact::email::DomainPolicy::new(
+                &settings.allow,
+                &settings.deny,
+                settings.disposable_list_path.as_deref(),
+            )
+        })
+        .transpose()
+        .map_err(|error| {
+            std::io::Error::other(format!(
+                "Unable to load disposable email domain list: {}",
+                error
+            ))
+        })?
+        .unwrap_or_default();
 
-    http::start(http_settings, email_service)
+    http::start(
+        settings.http,
+        settings.forms,
+        protection,
+        captcha,
+        spam_filter,
+        contact_policy,
+        domain_policy,
+        AttachmentPolicy::new(settings.attachments),
+        email_service,
+        outbox,
+        storage,
+        metrics,
+        readiness::Readiness::new(settings.readiness),
+    )
 }
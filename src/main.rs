@@ -8,7 +8,7 @@ async fn main() -> std::io::Result<()> {
     let (subscriber, _guard) = logging::get_subscriber(&settings.log);
     logging::init(subscriber);
 
-    let app = contact_api::start(settings.http, settings.email)?;
+    let app = contact_api::start(settings).await?;
 
     app.server.await
 }
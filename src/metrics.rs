@@ -0,0 +1,210 @@
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry, TextEncoder,
+};
+
+use super::spam::Verdict;
+
+/// The content type of the text exposition format `render` produces.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Counters and histograms exposed on `/metrics`, cheap to clone as every metric is shared.
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_request_duration: HistogramVec,
+    validation_failures: IntCounterVec,
+    send_duration: HistogramVec,
+    send_failures: IntCounterVec,
+    queue_depth: IntGauge,
+    spam_verdicts: IntCounterVec,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("contact_api".to_owned()), None)
+            .expect("Unable to create metrics registry.");
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests by route and status."),
+            &["method", "route", "status"],
+        )
+        .expect("Unable to create http requests metric.");
+        let http_request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency by route and status.",
+            ),
+            &["method", "route", "status"],
+        )
+        .expect("Unable to create http request duration metric.");
+        let validation_failures = IntCounterVec::new(
+            Opts::new(
+                "validation_failures_total",
+                "Rejected submission fields by field and error.",
+            ),
+            &["field", "error"],
+        )
+        .expect("Unable to create validation failures metric.");
+        let send_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "email_send_duration_seconds",
+                "Email send latency by transport.",
+            ),
+            &["transport"],
+        )
+        .expect("Unable to create email send duration metric.");
+        let send_failures = IntCounterVec::new(
+            Opts::new(
+                "email_send_failures_total",
+                "Failed email sends by transport.",
+            ),
+            &["transport"],
+        )
+        .expect("Unable to create email send failures metric.");
+        let queue_depth = IntGauge::new("outbox_pending", "Submissions waiting in the outbox.")
+            .expect("Unable to create outbox pending metric.");
+        let spam_verdicts = IntCounterVec::new(
+            Opts::new("spam_verdicts_total", "Spam verdicts given to submissions."),
+            &["verdict"],
+        )
+        .expect("Unable to create spam verdicts metric.");
+
+        for collector in [
+            Box::new(http_requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(http_request_duration.clone()),
+            Box::new(validation_failures.clone()),
+            Box::new(send_duration.clone()),
+            Box::new(send_failures.clone()),
+            Box::new(queue_depth.clone()),
+            Box::new(spam_verdicts.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("Unable to register metric.");
+        }
+
+        Self {
+            registry,
+            http_requests,
+            http_request_duration,
+            validation_failures,
+            send_duration,
+            send_failures,
+            queue_depth,
+            spam_verdicts,
+        }
+    }
+
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, elapsed: Duration) {
+        let status = status.to_string();
+        let labels = [method, route, status.as_str()];
+
+        self.http_requests.with_label_values(&labels).inc();
+        self.http_request_duration
+            .with_label_values(&labels)
+            .observe(elapsed.as_secs_f64());
+    }
+
+    pub fn validation_failed(&self, field: &str, error: &str) {
+        self.validation_failures
+            .with_label_values(&[field, error])
+            .inc();
+    }
+
+    /// Times a send through `transport`, counting it as failed when it errors.
+    pub async fn observe_send<T, E>(
+        &self,
+        transport: &str,
+        send: impl Future<Output = Result<T, E>>,
+    ) -> Result<T, E> {
+        let started = Instant::now();
+        let result = send.await;
+
+        self.send_duration
+            .with_label_values(&[transport])
+            .observe(started.elapsed().as_secs_f64());
+        if result.is_err() {
+            self.send_failures.with_label_values(&[transport]).inc();
+        }
+
+        result
+    }
+
+    pub fn set_queue_depth(&self, depth: i64) {
+        self.queue_depth.set(depth);
+    }
+
+    pub fn spam_verdict(&self, verdict: Verdict) {
+        let verdict = match verdict {
+            Verdict::Deliver => "deliver",
+            Verdict::Tag(_) => "tag",
+            Verdict::Quarantine(_) => "quarantine",
+            Verdict::Reject(_) => "reject",
+        };
+
+        self.spam_verdicts.with_label_values(&[verdict]).inc();
+    }
+
+    /// Every metric in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("Unable to encode metrics.");
+
+        String::from_utf8(buffer).expect("Metrics are not utf-8.")
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_in_the_text_format() {
+        let metrics = Metrics::new();
+        metrics.observe_request("POST", "/forms/{id}", 202, Duration::from_millis(20));
+        metrics.validation_failed("email", "email-missing-at-sign");
+        metrics.spam_verdict(Verdict::Tag(5));
+        metrics.set_queue_depth(3);
+
+        let rendered = metrics.render();
+
+        assert!(rendered.contains(
+            r#"contact_api_http_requests_total{method="POST",route="/forms/{id}",status="202"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"contact_api_validation_failures_total{error="email-missing-at-sign",field="email"} 1"#
+        ));
+        assert!(rendered.contains(r#"contact_api_spam_verdicts_total{verdict="tag"} 1"#));
+        assert!(rendered.contains("contact_api_outbox_pending 3"));
+    }
+
+    #[actix_rt::test]
+    async fn counts_failed_sends() {
+        let metrics = Metrics::new();
+
+        let _ = metrics
+            .observe_send("smtp", async { Err::<(), _>("Ruh-roh!") })
+            .await;
+        let _ = metrics
+            .observe_send("file", async { Ok::<_, ()>(()) })
+            .await;
+
+        let rendered = metrics.render();
+        assert!(rendered.contains(r#"contact_api_email_send_failures_total{transport="smtp"} 1"#));
+        assert!(!rendered.contains(r#"contact_api_email_send_failures_total{transport="file"}"#));
+        assert!(rendered
+            .contains(r#"contact_api_email_send_duration_seconds_count{transport="file"} 1"#));
+    }
+}
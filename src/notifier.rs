@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::sync::Arc;
+
+use futures_util::future::LocalBoxFuture;
+
+use super::domain::contact::Contact;
+use super::domain::metadata::Metadata;
+use super::settings::NotifyPolicy;
+
+/// A channel a submission is delivered through once it leaves the outbox.
+pub trait Notifier {
+    fn name(&self) -> &str;
+
+    /// `id` identifies the outbox entry, staying the same when delivery is retried.
+    fn notify<'a>(
+        &'a self,
+        id: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>>;
+}
+
+impl<T: Notifier> Notifier for Arc<T> {
+    fn name(&self) -> &str {
+        T::name(self)
+    }
+
+    fn notify<'a>(
+        &'a self,
+        id: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        T::notify(self, id, contact, metadata)
+    }
+}
+
+/// Identifies a delivery to receivers that deduplicate, staying the same across retries of an
+/// outbox entry. The submission time keeps keys from repeating should the outbox be recreated.
+pub fn delivery_key(id: i64, metadata: &Metadata) -> String {
+    format!("{}.{}", metadata.submitted_at.timestamp_millis(), id)
+}
+
+/// What one round of notifying achieved.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// Notifiers that succeeded this round, so later rounds can skip them.
+    pub delivered: Vec<String>,
+    pub result: Result<(), String>,
+}
+
+/// Fans a submission out to every notifier in parallel, succeeding according to the policy.
+pub struct Notifiers {
+    notifiers: Vec<Box<dyn Notifier>>,
+    policy: NotifyPolicy,
+}
+
+impl Notifiers {
+    pub fn new(notifiers: Vec<Box<dyn Notifier>>, policy: NotifyPolicy) -> Self {
+        Self { notifiers, policy }
+    }
+
+    /// Notifies all but the `delivered` notifiers, which count as having succeeded.
+    #[tracing::instrument(name = "Notify contact", skip(self, contact, metadata))]
+    pub async fn notify(
+        &self,
+        id: i64,
+        contact: &Contact,
+        metadata: &Metadata,
+        delivered: &[String],
+    ) -> Outcome {
+        let pending: Vec<_> = self
+            .notifiers
+            .iter()
+            .filter(|notifier| !delivered.iter().any(|name| name == notifier.name()))
+            .collect();
+
+        let results = futures_util::future::join_all(
+            pending
+                .iter()
+                .map(|notifier| notifier.notify(id, contact, metadata)),
+        )
+        .await;
+
+        let mut succeeded = Vec::new();
+        let mut failures = Vec::new();
+        for (notifier, result) in pending.iter().zip(results) {
+            match result {
+                Ok(()) => succeeded.push(notifier.name().to_owned()),
+                Err(error) => failures.push(format!("{}: {}", notifier.name(), error)),
+            }
+        }
+
+        for failure in &failures {
+            tracing::warn!("Notifier failed, {}", failure);
+        }
+
+        let result = match self.policy {
+            NotifyPolicy::All => failures.is_empty(),
+            NotifyPolicy::Any => failures.len() < self.notifiers.len() || self.notifiers.is_empty(),
+            NotifyPolicy::BestEffort => true,
+        };
+
+        Outcome {
+            delivered: succeeded,
+            result: if result {
+                Ok(())
+            } else {
+                Err(failures.join(", "))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{contact, metadata};
+    use futures_util::FutureExt;
+
+    struct Fake(&'static str, bool);
+
+    impl Notifier for Fake {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn notify<'a>(
+            &'a self,
+            _: i64,
+            _: &'a Contact,
+            _: &'a Metadata,
+        ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+            let result = if self.1 {
+                Ok(())
+            } else {
+                Err("Ruh-roh!".into())
+            };
+            async move { result }.boxed_local()
+        }
+    }
+
+    async fn notify(policy: NotifyPolicy, results: &[bool], delivered: &[&str]) -> Outcome {
+        let names = ["chat", "email", "webhook"];
+        let notifiers = names
+            .iter()
+            .zip(results)
+            .map(|(name, ok)| Box::new(Fake(name, *ok)) as Box<dyn Notifier>)
+            .collect();
+        let (contact, metadata) = (contact(), metadata());
+
+        let delivered: Vec<String> = delivered.iter().map(|name| name.to_string()).collect();
+
+        Notifiers::new(notifiers, policy)
+            .notify(1, &contact, &metadata, &delivered)
+            .await
+    }
+
+    #[actix_rt::test]
+    async fn all_requires_every_notifier_to_succeed() {
+        assert_eq!(
+            Ok(()),
+            notify(NotifyPolicy::All, &[true, true], &[]).await.result
+        );
+        assert_eq!(
+            Err("email: Ruh-roh!".to_owned()),
+            notify(NotifyPolicy::All, &[true, false], &[]).await.result
+        );
+    }
+
+    #[actix_rt::test]
+    async fn any_requires_one_notifier_to_succeed() {
+        assert_eq!(
+            Ok(()),
+            notify(NotifyPolicy::Any, &[false, true], &[]).await.result
+        );
+        assert_eq!(
+            Err("chat: Ruh-roh!, email: Ruh-roh!".to_owned()),
+            notify(NotifyPolicy::Any, &[false, false], &[]).await.result
+        );
+    }
+
+    #[actix_rt::test]
+    async fn best_effort_always_succeeds() {
+        assert_eq!(
+            Ok(()),
+            notify(NotifyPolicy::BestEffort, &[false, false], &[])
+                .await
+                .result
+        );
+    }
+
+    #[actix_rt::test]
+    async fn delivered_notifiers_are_skipped_and_count_as_succeeded() {
+        let outcome = notify(NotifyPolicy::All, &[false, true, true], &["chat"]).await;
+
+        assert_eq!(Ok(()), outcome.result);
+        assert_eq!(vec!["email", "webhook"], outcome.delivered);
+    }
+}
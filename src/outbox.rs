@@ -0,0 +1,736 @@
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{Sqlite, SqliteConnectOptions, SqlitePool, SqlitePoolOptions};
+use tokio::sync::Notify;
+
+use chrono::{TimeZone, Utc};
+
+use super::domain::attachment::Attachment;
+use super::domain::contact::{Contact, ContactPolicy};
+use super::domain::metadata::Metadata;
+use super::email::EmailService;
+use super::notifier::Notifiers;
+use super::settings::OutboxSettings;
+use super::storage::{Status as SubmissionStatus, Storage};
+
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations/outbox");
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Status {
+    Pending,
+    Sent,
+    Dead,
+    Quarantined,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Pending => "pending",
+            Status::Sent => "sent",
+            Status::Dead => "dead",
+            Status::Quarantined => "quarantined",
+        }
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Job {
+    id: i64,
+    email: String,
+    name: String,
+    message: String,
+    attempts: i64,
+    created_at: i64,
+    form_id: Option<String>,
+    fields: String,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+    referer: Option<String>,
+    spam_score: Option<i64>,
+    submission_id: Option<i64>,
+}
+
+impl Job {
+    fn metadata(&self) -> Metadata {
+        Metadata {
+            submitted_at: Utc
+                .timestamp_millis_opt(self.created_at)
+                .single()
+                .unwrap_or_else(Utc::now),
+            form_id: self.form_id.clone(),
+            client_ip: self.client_ip.clone(),
+            user_agent: self.user_agent.clone(),
+            referer: self.referer.clone(),
+            spam_score: self.spam_score.map(|score| score as u32),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Outbox {
+    pool: SqlitePool,
+    notify: Arc<Notify>,
+    storage: Option<Storage>,
+    max_attempts: u32,
+    retry_delay: Duration,
+    max_retry_delay: Duration,
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch.")
+        .as_millis() as i64
+}
+
+impl Outbox {
+    pub async fn connect(settings: &OutboxSettings) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::new()
+            .filename(&settings.path)
+            .create_if_missing(true);
+
+        let pool = SqlitePoolOptions::new().connect_with(options).await?;
+        MIGRATOR.run(&pool).await?;
+
+        Ok(Self {
+            pool,
+            notify: Arc::new(Notify::new()),
+            storage: None,
+            max_attempts: settings.max_attempts,
+            retry_delay: Duration::from_secs(settings.retry_delay_secs),
+            max_retry_delay: Duration::from_secs(settings.max_retry_delay_secs),
+        })
+    }
+
+    /// Keeps the stored submission's delivery status in step with its outbox entry.
+    pub fn with_storage(self, storage: Option<Storage>) -> Self {
+        Self { storage, ..self }
+    }
+
+    async fn insert(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+        submission_id: Option<i64>,
+        status: Status,
+    ) -> Result<i64, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        let id = sqlx::query(
+            "INSERT INTO outbox
+             (email, name, message, form_id, fields, client_ip, user_agent, referer, spam_score,
+              submission_id, status, created_at, next_attempt_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(contact.email.as_ref())
+        .bind(contact.name.as_ref())
+        .bind(contact.message.as_ref())
+        .bind(&metadata.form_id)
+        .bind(serde_json::to_string(&contact.fields).expect("Fields are always serializable."))
+        .bind(&metadata.client_ip)
+        .bind(&metadata.user_agent)
+        .bind(&metadata.referer)
+        .bind(metadata.spam_score)
+        .bind(submission_id)
+        .bind(status.as_str())
+        .bind(metadata.submitted_at.timestamp_millis())
+        .bind(now_millis())
+        .execute(&mut *transaction)
+        .await?
+        .last_insert_rowid();
+
+        for attachment in &contact.attachments {
+            sqlx::query(
+                "INSERT INTO outbox_attachments (outbox_id, filename, content_type, content)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(&attachment.filename)
+            .bind(&attachment.content_type)
+            .bind(&attachment.content)
+            .execute(&mut *transaction)
+            .await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(id)
+    }
+
+    #[tracing::instrument(name = "Enqueue contact", skip(self))]
+    pub async fn enqueue(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+        submission_id: Option<i64>,
+    ) -> Result<i64, sqlx::Error> {
+        let id = self
+            .insert(contact, metadata, submission_id, Status::Pending)
+            .await?;
+
+        tracing::info!("Contact queued as outbox entry {}.", id);
+        self.notify.notify_one();
+
+        Ok(id)
+    }
+
+    /// Keeps a contact in the outbox without ever delivering it.
+    #[tracing::instrument(name = "Quarantine contact", skip(self))]
+    pub async fn quarantine(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+        submission_id: Option<i64>,
+    ) -> Result<i64, sqlx::Error> {
+        let id = self
+            .insert(contact, metadata, submission_id, Status::Quarantined)
+            .await?;
+
+        tracing::info!("Contact quarantined as outbox entry {}.", id);
+
+        Ok(id)
+    }
+
+    async fn next_due(&self) -> Result<Option<Job>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT id, email, name, message, attempts, created_at, form_id, fields,
+                    client_ip, user_agent, referer, spam_score, submission_id
+             FROM outbox
+             WHERE status = ? AND next_attempt_at <= ?
+             ORDER BY next_attempt_at, id
+             LIMIT 1",
+        )
+        .bind(Status::Pending.as_str())
+        .bind(now_millis())
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn attachments(&self, job: &Job) -> Result<Vec<Attachment>, sqlx::Error> {
+        let rows: Vec<(String, String, Vec<u8>)> = sqlx::query_as(
+            "SELECT filename, content_type, content FROM outbox_attachments
+             WHERE outbox_id = ?
+             ORDER BY id",
+        )
+        .bind(job.id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(filename, content_type, content)| Attachment {
+                filename,
+                content_type,
+                content,
+            })
+            .collect())
+    }
+
+    async fn next_attempt_at(&self) -> Result<Option<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT MIN(next_attempt_at) FROM outbox WHERE status = ?")
+            .bind(Status::Pending.as_str())
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Notifiers that already succeeded for an entry, which retries skip.
+    async fn delivered(&self, job: &Job) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT notifier FROM outbox_deliveries WHERE outbox_id = ?")
+            .bind(job.id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn record_delivered<S: AsRef<str>>(
+        &self,
+        job: &Job,
+        notifiers: &[S],
+    ) -> Result<(), sqlx::Error> {
+        for notifier in notifiers {
+            sqlx::query(
+                "INSERT OR IGNORE INTO outbox_deliveries (outbox_id, notifier) VALUES (?, ?)",
+            )
+            .bind(job.id)
+            .bind(notifier.as_ref())
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// How many entries are waiting to be delivered, including those backing off.
+    pub async fn depth(&self) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM outbox WHERE status = ?")
+            .bind(Status::Pending.as_str())
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Deletes every entry of a stored submission, with its attachments and deliveries, so
+    /// deleting a submission leaves no copy of it behind.
+    #[tracing::instrument(name = "Forget submission", skip(self))]
+    pub async fn forget(&self, submission_id: i64) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        for table in &["outbox_attachments", "outbox_deliveries"] {
+            sqlx::query(&format!(
+                "DELETE FROM {} WHERE outbox_id IN (SELECT id FROM outbox WHERE submission_id = ?)",
+                table
+            ))
+            .bind(submission_id)
+            .execute(&mut *transaction)
+            .await?;
+        }
+        sqlx::query("DELETE FROM outbox WHERE submission_id = ?")
+            .bind(submission_id)
+            .execute(&mut *transaction)
+            .await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    /// Attachments are only kept until an entry is sent or dead, as nothing reads them after.
+    async fn delete_attachments(
+        transaction: &mut sqlx::Transaction<'_, Sqlite>,
+        job: &Job,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM outbox_attachments WHERE outbox_id = ?")
+            .bind(job.id)
+            .execute(&mut **transaction)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn mark_sent(&self, job: &Job) -> Result<(), sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query("UPDATE outbox SET status = ?, attempts = ?, last_error = NULL WHERE id = ?")
+            .bind(Status::Sent.as_str())
+            .bind(job.attempts + 1)
+            .bind(job.id)
+            .execute(&mut *transaction)
+            .await?;
+        Self::delete_attachments(&mut transaction, job).await?;
+
+        transaction.commit().await?;
+
+        Ok(())
+    }
+
+    async fn mark_failed(&self, job: &Job, error: &str) -> Result<Status, sqlx::Error> {
+        let attempts = job.attempts + 1;
+
+        let status = if attempts >= i64::from(self.max_attempts) {
+            Status::Dead
+        } else {
+            Status::Pending
+        };
+
+        let next_attempt_at = now_millis() + self.retry_delay(attempts as u32).as_millis() as i64;
+
+        let mut transaction = self.pool.begin().await?;
+
+        sqlx::query(
+            "UPDATE outbox SET status = ?, attempts = ?, last_error = ?, next_attempt_at = ?
+             WHERE id = ?",
+        )
+        .bind(status.as_str())
+        .bind(attempts)
+        .bind(error)
+        .bind(next_attempt_at)
+        .bind(job.id)
+        .execute(&mut *transaction)
+        .await?;
+        if status == Status::Dead {
+            Self::delete_attachments(&mut transaction, job).await?;
+        }
+
+        transaction.commit().await?;
+
+        Ok(status)
+    }
+
+    async fn update_submission(&self, job: &Job, status: SubmissionStatus) {
+        let (storage, id) = match (&self.storage, job.submission_id) {
+            (Some(storage), Some(id)) => (storage, id),
+            _ => return,
+        };
+
+        if let Err(error) = storage.set_status(id, status).await {
+            tracing::error!("Unable to update stored submission: {:?}", error);
+        }
+    }
+
+    fn retry_delay(&self, attempts: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+        self.retry_delay
+            .checked_mul(factor)
+            .map_or(self.max_retry_delay, |delay| {
+                delay.min(self.max_retry_delay)
+            })
+    }
+
+    async fn wait(&self) {
+        let next_attempt_at = self.next_attempt_at().await.unwrap_or_else(|error| {
+            tracing::error!("Unable to read outbox: {:?}", error);
+            None
+        });
+
+        match next_attempt_at {
+            Some(at) => {
+                let delay = Duration::from_millis((at - now_millis()).max(0) as u64);
+                tokio::select! {
+                    _ = self.notify.notified() => {},
+                    _ = tokio::time::sleep(delay) => {},
+                }
+            }
+            None => self.notify.notified().await,
+        }
+    }
+
+    #[tracing::instrument(name = "Deliver outbox entry", skip(self, job, notifiers, email_service), fields(id = job.id, attempt = job.attempts + 1))]
+    async fn deliver(&self, job: Job, notifiers: &Notifiers, email_service: &EmailService) {
+        let metadata = job.metadata();
+
+        // Validated when queued, and the limits may have changed since.
+        let contact = match Contact::new(
+            &job.email,
+            &job.name,
+            &job.message,
+            &ContactPolicy::UNLIMITED,
+        ) {
+            Ok(contact) => {
+                contact.with_fields(serde_json::from_str(&job.fields).unwrap_or_else(|error| {
+                    tracing::warn!("Unable to read outbox entry fields: {}", error);
+                    Default::default()
+                }))
+            }
+            Err(error) => {
+                return self
+                    .record_failure(&job, format!("Invalid contact: {:?}", error))
+                    .await
+            }
+        };
+
+        let contact = match self.attachments(&job).await {
+            Ok(attachments) => contact.with_attachments(attachments),
+            Err(error) => return self.record_failure(&job, error.to_string()).await,
+        };
+
+        let delivered = match self.delivered(&job).await {
+            Ok(delivered) => delivered,
+            Err(error) => return self.record_failure(&job, error.to_string()).await,
+        };
+
+        let outcome = notifiers
+            .notify(job.id, &contact, &metadata, &delivered)
+            .await;
+        // Under `best_effort` an entry is sent even when every notifier failed, and nobody has
+        // actually received the submission the acknowledgement would confirm.
+        let received = !delivered.is_empty() || !outcome.delivered.is_empty();
+        if let Err(error) = self.record_delivered(&job, &outcome.delivered).await {
+            tracing::error!("Unable to record delivered notifiers: {:?}", error);
+        }
+
+        if let Err(error) = outcome.result {
+            return self.record_failure(&job, error).await;
+        }
+
+        tracing::info!("Outbox entry delivered.");
+        if let Err(error) = self.mark_sent(&job).await {
+            tracing::error!("Unable to update outbox entry: {:?}", error);
+        }
+        self.update_submission(&job, SubmissionStatus::Sent).await;
+
+        if !received {
+            tracing::info!("Acknowledgement skipped, no notifier delivered the submission.");
+            return;
+        }
+        if let Err(error) = email_service.acknowledge(&contact, &metadata).await {
+            tracing::warn!("Unable to send acknowledgement: {}", error);
+        }
+    }
+
+    async fn record_failure(&self, job: &Job, error: String) {
+        match self.mark_failed(job, &error).await {
+            Ok(Status::Dead) => {
+                tracing::error!("Outbox entry failed, giving up: {}", error);
+                self.update_submission(job, SubmissionStatus::Dead).await;
+            }
+            Ok(_) => tracing::warn!("Outbox entry failed, will retry: {}", error),
+            Err(update_error) => {
+                tracing::error!("Unable to update outbox entry: {:?}", update_error)
+            }
+        }
+    }
+
+    /// Delivers entries through the notifiers, acknowledging them to submitters once delivered.
+    pub async fn run(self, notifiers: Notifiers, email_service: Arc<EmailService>) {
+        loop {
+            match self.next_due().await {
+                Ok(Some(job)) => self.deliver(job, &notifiers, &email_service).await,
+                Ok(None) => self.wait().await,
+                Err(error) => {
+                    tracing::error!("Unable to read outbox: {:?}", error);
+                    self.wait().await
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{contact, metadata};
+
+    async fn outbox(max_attempts: u32) -> Outbox {
+        let path = std::env::temp_dir().join(format!("outbox-{}.db", uuid::Uuid::new_v4()));
+
+        Outbox::connect(&OutboxSettings {
+            path: path.to_string_lossy().into_owned(),
+            max_attempts,
+            retry_delay_secs: 0,
+            max_retry_delay_secs: 0,
+        })
+        .await
+        .expect("Unable to open outbox.")
+    }
+
+    async fn status(outbox: &Outbox, id: i64) -> Status {
+        let status: String = sqlx::query_scalar("SELECT status FROM outbox WHERE id = ?")
+            .bind(id)
+            .fetch_one(&outbox.pool)
+            .await
+            .unwrap();
+
+        match status.as_str() {
+            "pending" => Status::Pending,
+            "sent" => Status::Sent,
+            "dead" => Status::Dead,
+            "quarantined" => Status::Quarantined,
+            other => panic!("Unknown status {}", other),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn enqueued_contacts_are_pending_and_due() {
+        let outbox = outbox(3).await;
+
+        let id = outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+
+        assert_eq!(Status::Pending, status(&outbox, id).await);
+
+        let job = outbox.next_due().await.unwrap().unwrap();
+        assert_eq!(id, job.id);
+        assert_eq!(metadata(), job.metadata());
+        assert_eq!("{}", job.fields);
+    }
+
+    #[actix_rt::test]
+    async fn attachments_are_kept_with_their_entry() {
+        let outbox = outbox(3).await;
+        let attachments = vec![
+            Attachment {
+                filename: "clue.txt".to_owned(),
+                content_type: "text/plain".to_owned(),
+                content: b"Jinkies!".to_vec(),
+            },
+            Attachment {
+                filename: "clue.png".to_owned(),
+                content_type: "image/png".to_owned(),
+                content: b"\x89PNG\r\n\x1a\n".to_vec(),
+            },
+        ];
+
+        outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+        outbox
+            .enqueue(
+                &contact().with_attachments(attachments.clone()),
+                &metadata(),
+                Some(7),
+            )
+            .await
+            .unwrap();
+
+        let first = outbox.next_due().await.unwrap().unwrap();
+        assert!(outbox.attachments(&first).await.unwrap().is_empty());
+        outbox.mark_sent(&first).await.unwrap();
+
+        let second = outbox.next_due().await.unwrap().unwrap();
+        assert_eq!(None, first.submission_id);
+        assert_eq!(Some(7), second.submission_id);
+        assert_eq!(attachments, outbox.attachments(&second).await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn attachments_are_deleted_once_sent_or_dead() {
+        let outbox = outbox(1).await;
+        let attachment = Attachment {
+            filename: "clue.txt".to_owned(),
+            content_type: "text/plain".to_owned(),
+            content: b"Jinkies!".to_vec(),
+        };
+        let contact = contact().with_attachments(vec![attachment]);
+
+        outbox.enqueue(&contact, &metadata(), None).await.unwrap();
+        outbox.enqueue(&contact, &metadata(), None).await.unwrap();
+
+        let sent = outbox.next_due().await.unwrap().unwrap();
+        outbox.mark_sent(&sent).await.unwrap();
+        let dead = outbox.next_due().await.unwrap().unwrap();
+        assert_eq!(
+            Status::Dead,
+            outbox.mark_failed(&dead, "boom").await.unwrap()
+        );
+
+        assert!(outbox.attachments(&sent).await.unwrap().is_empty());
+        assert!(outbox.attachments(&dead).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn forgotten_submissions_leave_nothing_in_the_outbox() {
+        let outbox = outbox(3).await;
+        let attachment = Attachment {
+            filename: "clue.txt".to_owned(),
+            content_type: "text/plain".to_owned(),
+            content: b"Jinkies!".to_vec(),
+        };
+        let contact = contact().with_attachments(vec![attachment]);
+
+        let forgotten = outbox
+            .enqueue(&contact, &metadata(), Some(7))
+            .await
+            .unwrap();
+        let kept = outbox
+            .enqueue(&contact, &metadata(), Some(8))
+            .await
+            .unwrap();
+        let job = outbox.next_due().await.unwrap().unwrap();
+        outbox.record_delivered(&job, &["email"]).await.unwrap();
+
+        outbox.forget(7).await.unwrap();
+
+        for table in &["outbox_attachments", "outbox_deliveries"] {
+            let query = format!("SELECT COUNT(*) FROM {} WHERE outbox_id = ?", table);
+            let rows: i64 = sqlx::query_scalar(&query)
+                .bind(forgotten)
+                .fetch_one(&outbox.pool)
+                .await
+                .unwrap();
+            assert_eq!(0, rows, "{} still has rows", table);
+        }
+        assert_eq!(kept, outbox.next_due().await.unwrap().unwrap().id);
+        assert_eq!(1, outbox.depth().await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn depth_counts_pending_contacts() {
+        let outbox = outbox(3).await;
+        let (contact, metadata) = (contact(), metadata());
+
+        outbox.enqueue(&contact, &metadata, None).await.unwrap();
+        outbox.enqueue(&contact, &metadata, None).await.unwrap();
+        outbox.quarantine(&contact, &metadata, None).await.unwrap();
+
+        assert_eq!(2, outbox.depth().await.unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn sent_contacts_are_no_longer_due() {
+        let outbox = outbox(3).await;
+
+        let id = outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+        let job = outbox.next_due().await.unwrap().unwrap();
+        outbox.mark_sent(&job).await.unwrap();
+
+        assert_eq!(Status::Sent, status(&outbox, id).await);
+        assert!(outbox.next_due().await.unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn quarantined_contacts_are_never_due() {
+        let outbox = outbox(3).await;
+
+        let id = outbox
+            .quarantine(&contact(), &metadata(), None)
+            .await
+            .unwrap();
+
+        assert_eq!(Status::Quarantined, status(&outbox, id).await);
+        assert!(outbox.next_due().await.unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn failed_contacts_are_dead_lettered_after_max_attempts() {
+        let outbox = outbox(2).await;
+
+        let id = outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+
+        let job = outbox.next_due().await.unwrap().unwrap();
+        assert_eq!(
+            Status::Pending,
+            outbox.mark_failed(&job, "boom").await.unwrap()
+        );
+
+        let job = outbox.next_due().await.unwrap().unwrap();
+        assert_eq!(1, job.attempts);
+        assert_eq!(
+            Status::Dead,
+            outbox.mark_failed(&job, "boom").await.unwrap()
+        );
+
+        assert_eq!(Status::Dead, status(&outbox, id).await);
+        assert!(outbox.next_due().await.unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn delivered_notifiers_are_remembered_per_entry() {
+        let outbox = outbox(3).await;
+
+        outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+        outbox.enqueue(&contact(), &metadata(), None).await.unwrap();
+        let job = outbox.next_due().await.unwrap().unwrap();
+
+        outbox.record_delivered(&job, &["email"]).await.unwrap();
+        outbox
+            .record_delivered(&job, &["email", "webhooks"])
+            .await
+            .unwrap();
+
+        let mut delivered = outbox.delivered(&job).await.unwrap();
+        delivered.sort();
+        assert_eq!(vec!["email", "webhooks"], delivered);
+
+        outbox.mark_sent(&job).await.unwrap();
+        let other = outbox.next_due().await.unwrap().unwrap();
+        assert!(outbox.delivered(&other).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn retry_delay_backs_off_exponentially_up_to_the_max() {
+        let outbox = Outbox {
+            retry_delay: Duration::from_secs(5),
+            max_retry_delay: Duration::from_secs(60),
+            ..outbox(3).await
+        };
+
+        assert_eq!(Duration::from_secs(5), outbox.retry_delay(1));
+        assert_eq!(Duration::from_secs(10), outbox.retry_delay(2));
+        assert_eq!(Duration::from_secs(40), outbox.retry_delay(4));
+        assert_eq!(Duration::from_secs(60), outbox.retry_delay(5));
+        assert_eq!(Duration::from_secs(60), outbox.retry_delay(64));
+    }
+}
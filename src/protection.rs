@@ -0,0 +1,215 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::settings::BotProtectionSettings;
+
+type HmacSha256 = Hmac<Sha256>;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum BotError {
+    HoneypotFilled,
+    TokenMissing,
+    TokenInvalid,
+    TokenExpired,
+    TooFast,
+}
+
+/// Honeypot and signed timestamp checks for submissions, disabled when not configured.
+pub struct BotProtection {
+    honeypot_field: Option<String>,
+    token_field: Option<String>,
+    secret: Option<Vec<u8>>,
+    min_fill_secs: u64,
+    max_token_age_secs: u64,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is before the unix epoch.")
+        .as_secs()
+}
+
+fn is_filled(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Null => false,
+        serde_json::Value::String(value) => !value.trim().is_empty(),
+        _ => true,
+    }
+}
+
+impl BotProtection {
+    pub fn new(settings: Option<BotProtectionSettings>) -> Self {
+        match settings {
+            Some(settings) => Self {
+                honeypot_field: settings.honeypot_field,
+                token_field: Some(settings.token_field),
+                secret: settings.token_secret.map(String::into_bytes),
+                min_fill_secs: settings.min_fill_secs,
+                max_token_age_secs: settings.max_token_age_secs,
+            },
+            None => Self {
+                honeypot_field: None,
+                token_field: None,
+                secret: None,
+                min_fill_secs: 0,
+                max_token_age_secs: 0,
+            },
+        }
+    }
+
+    fn mac(secret: &[u8], issued_at: u64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(secret).expect("Hmac accepts keys of any size.");
+        mac.update(issued_at.to_string().as_bytes());
+        mac
+    }
+
+    fn token_at(&self, issued_at: u64) -> Option<String> {
+        self.secret.as_ref().map(|secret| {
+            let signature = Self::mac(secret, issued_at).finalize().into_bytes();
+            format!("{}.{}", issued_at, hex::encode(signature))
+        })
+    }
+
+    pub fn issue_token(&self) -> Option<String> {
+        self.token_at(now_secs())
+    }
+
+    fn verify_token(&self, secret: &[u8], token: &str, now: u64) -> Result<(), BotError> {
+        let (issued_at, signature) = token.split_once('.').ok_or(BotError::TokenInvalid)?;
+        let signature = hex::decode(signature).map_err(|_| BotError::TokenInvalid)?;
+        let issued_at: u64 = issued_at.parse().map_err(|_| BotError::TokenInvalid)?;
+
+        Self::mac(secret, issued_at)
+            .verify_slice(&signature)
+            .map_err(|_| BotError::TokenInvalid)?;
+
+        let age = now.checked_sub(issued_at).ok_or(BotError::TokenInvalid)?;
+        if age < self.min_fill_secs {
+            Err(BotError::TooFast)
+        } else if age > self.max_token_age_secs {
+            Err(BotError::TokenExpired)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn check_at(
+        &self,
+        fields: &mut BTreeMap<String, serde_json::Value>,
+        now: u64,
+    ) -> Result<(), BotError> {
+        let honeypot = self
+            .honeypot_field
+            .as_ref()
+            .and_then(|field| fields.remove(field));
+        let token = self
+            .token_field
+            .as_ref()
+            .and_then(|field| fields.remove(field));
+
+        if honeypot.as_ref().is_some_and(is_filled) {
+            return Err(BotError::HoneypotFilled);
+        }
+
+        match (&self.secret, token) {
+            (None, _) => Ok(()),
+            (Some(secret), Some(serde_json::Value::String(token))) => {
+                self.verify_token(secret, &token, now)
+            }
+            (Some(_), _) => Err(BotError::TokenMissing),
+        }
+    }
+
+    /// Checks a submission's extra fields, removing the honeypot and token fields from them.
+    pub fn check(&self, fields: &mut BTreeMap<String, serde_json::Value>) -> Result<(), BotError> {
+        self.check_at(fields, now_secs())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> BotProtectionSettings {
+        BotProtectionSettings {
+            honeypot_field: Some("website".to_owned()),
+            token_field: "token".to_owned(),
+            token_secret: None,
+            min_fill_secs: 3,
+            max_token_age_secs: 60,
+        }
+    }
+
+    fn protection(secret: Option<&str>) -> BotProtection {
+        BotProtection::new(Some(BotProtectionSettings {
+            token_secret: secret.map(String::from),
+            ..settings()
+        }))
+    }
+
+    fn fields(values: &[(&str, &str)]) -> BTreeMap<String, serde_json::Value> {
+        values
+            .iter()
+            .map(|(name, value)| (name.to_string(), serde_json::json!(value)))
+            .collect()
+    }
+
+    #[test]
+    fn disabled_protection_accepts_everything() {
+        let protection = BotProtection::new(None);
+        let mut fields = fields(&[("website", "spam.example")]);
+
+        assert_eq!(Ok(()), protection.check_at(&mut fields, 1000));
+        assert!(fields.contains_key("website"));
+        assert_eq!(None, protection.issue_token());
+    }
+
+    #[test]
+    fn filled_honeypot_is_a_bot() {
+        let mut filled = fields(&[("website", "spam.example")]);
+        let mut empty = fields(&[("website", " ")]);
+
+        assert_eq!(
+            Err(BotError::HoneypotFilled),
+            protection(None).check_at(&mut filled, 1000)
+        );
+        assert_eq!(Ok(()), protection(None).check_at(&mut empty, 1000));
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn tokens_must_be_present_and_signed() {
+        let forged = protection(Some("guess")).token_at(1000).unwrap();
+        let protection = protection(Some("secret"));
+
+        assert_eq!(
+            Err(BotError::TokenMissing),
+            protection.check_at(&mut fields(&[]), 1010)
+        );
+        assert_eq!(
+            Err(BotError::TokenInvalid),
+            protection.check_at(&mut fields(&[("token", "1000.zz")]), 1010)
+        );
+        assert_eq!(
+            Err(BotError::TokenInvalid),
+            protection.check_at(&mut fields(&[("token", &forged)]), 1010)
+        );
+    }
+
+    #[test]
+    fn tokens_enforce_the_fill_time_window() {
+        let protection = protection(Some("secret"));
+        let token = protection.token_at(1000).unwrap();
+        let check = |now| protection.check_at(&mut fields(&[("token", &token)]), now);
+
+        assert_eq!(Err(BotError::TokenInvalid), check(999));
+        assert_eq!(Err(BotError::TooFast), check(1002));
+        assert_eq!(Ok(()), check(1003));
+        assert_eq!(Ok(()), check(1060));
+        assert_eq!(Err(BotError::TokenExpired), check(1061));
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::email::EmailService;
+use super::outbox::Outbox;
+use super::settings::{ReadinessCheck, ReadinessSettings};
+use super::storage::Storage;
+
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(5);
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum Status {
+    Up,
+    Down,
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+struct Check {
+    status: Status,
+    required: bool,
+    latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(serde::Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    ready: bool,
+    checks: BTreeMap<&'static str, Check>,
+}
+
+impl Report {
+    fn new(checks: BTreeMap<&'static str, Check>) -> Self {
+        Self {
+            ready: checks
+                .values()
+                .all(|check| check.status == Status::Up || !check.required),
+            checks,
+        }
+    }
+
+    pub fn is_ready(&self) -> bool {
+        self.ready
+    }
+}
+
+/// Checks what delivering submissions depends on, reusing the last report for `cache_ttl` so
+/// frequent probes don't hammer the smtp relay.
+pub struct Readiness {
+    cache_ttl: Duration,
+    timeout: Duration,
+    optional: Vec<ReadinessCheck>,
+    cached: Mutex<Option<(Instant, Report)>>,
+}
+
+impl Readiness {
+    pub fn new(settings: Option<ReadinessSettings>) -> Self {
+        let (cache_ttl, timeout, optional) = match settings {
+            Some(settings) => (
+                Duration::from_secs(settings.cache_ttl_secs),
+                Duration::from_secs(settings.timeout_secs),
+                settings.optional,
+            ),
+            None => (DEFAULT_CACHE_TTL, DEFAULT_TIMEOUT, Vec::new()),
+        };
+
+        Self {
+            cache_ttl,
+            timeout,
+            optional,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn cached(&self, now: Instant) -> Option<Report> {
+        self.cached
+            .lock()
+            .expect("Readiness cache is poisoned.")
+            .as_ref()
+            .filter(|(checked_at, _)| now.duration_since(*checked_at) < self.cache_ttl)
+            .map(|(_, report)| report.clone())
+    }
+
+    async fn check<E: Display>(
+        &self,
+        kind: ReadinessCheck,
+        check: impl Future<Output = Result<(), E>>,
+    ) -> Check {
+        let started = Instant::now();
+        let result = match tokio::time::timeout(self.timeout, check).await {
+            Ok(result) => result.map_err(|error| error.to_string()),
+            Err(_) => Err("Timed out.".to_owned()),
+        };
+
+        if let Err(error) = &result {
+            tracing::warn!("Readiness check {:?} failed: {}", kind, error);
+        }
+
+        Check {
+            status: if result.is_ok() {
+                Status::Up
+            } else {
+                Status::Down
+            },
+            required: !self.optional.contains(&kind),
+            latency_ms: started.elapsed().as_millis() as u64,
+            error: result.err(),
+        }
+    }
+
+    #[tracing::instrument(name = "Check readiness", skip(self, email_service, outbox, storage))]
+    pub async fn report(
+        &self,
+        email_service: &EmailService,
+        outbox: &Outbox,
+        storage: Option<&Storage>,
+    ) -> Report {
+        if let Some(report) = self.cached(Instant::now()) {
+            return report;
+        }
+
+        let (smtp, backup_dir, outbox, storage) = futures_util::join!(
+            self.check(
+                ReadinessCheck::Smtp,
+                email_service.test_connection(self.timeout)
+            ),
+            self.check(ReadinessCheck::BackupDir, email_service.test_backup_dir()),
+            self.check(ReadinessCheck::Outbox, outbox.ping()),
+            async {
+                match storage {
+                    Some(storage) => {
+                        Some(self.check(ReadinessCheck::Storage, storage.ping()).await)
+                    }
+                    None => None,
+                }
+            },
+        );
+
+        let mut checks = BTreeMap::new();
+        checks.insert("smtp", smtp);
+        checks.insert("backup_dir", backup_dir);
+        checks.insert("outbox", outbox);
+        if let Some(storage) = storage {
+            checks.insert("storage", storage);
+        }
+
+        let report = Report::new(checks);
+        *self.cached.lock().expect("Readiness cache is poisoned.") =
+            Some((Instant::now(), report.clone()));
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(status: Status, required: bool) -> Check {
+        Check {
+            status,
+            required,
+            latency_ms: 1,
+            error: None,
+        }
+    }
+
+    fn report(checks: &[(&'static str, Check)]) -> Report {
+        Report::new(checks.iter().cloned().collect())
+    }
+
+    #[test]
+    fn required_checks_must_be_up() {
+        assert!(report(&[("smtp", check(Status::Up, true))]).is_ready());
+        assert!(!report(&[
+            ("smtp", check(Status::Down, true)),
+            ("outbox", check(Status::Up, true))
+        ])
+        .is_ready());
+    }
+
+    #[test]
+    fn optional_checks_may_be_down() {
+        assert!(report(&[
+            ("smtp", check(Status::Down, false)),
+            ("outbox", check(Status::Up, true))
+        ])
+        .is_ready());
+    }
+
+    #[test]
+    fn reports_are_cached_for_the_ttl() {
+        let readiness = Readiness::new(Some(ReadinessSettings {
+            cache_ttl_secs: 5,
+            timeout_secs: 1,
+            optional: vec![],
+        }));
+        let checked_at = Instant::now();
+        let cached = report(&[("smtp", check(Status::Up, true))]);
+        *readiness.cached.lock().unwrap() = Some((checked_at, cached.clone()));
+
+        assert_eq!(Some(cached), readiness.cached(checked_at));
+        assert_eq!(None, readiness.cached(checked_at + Duration::from_secs(5)));
+    }
+
+    #[actix_rt::test]
+    async fn slow_checks_time_out() {
+        let readiness = Readiness::new(Some(ReadinessSettings {
+            cache_ttl_secs: 0,
+            timeout_secs: 0,
+            optional: vec![ReadinessCheck::Smtp],
+        }));
+
+        let check = readiness
+            .check(ReadinessCheck::Smtp, async {
+                tokio::time::sleep(Duration::from_secs(1)).await;
+                Ok::<_, String>(())
+            })
+            .await;
+
+        assert_eq!(Status::Down, check.status);
+        assert!(!check.required);
+        assert_eq!(Some("Timed out.".to_owned()), check.error);
+    }
+}
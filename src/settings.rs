@@ -1,4 +1,8 @@
-use std::{ffi::OsString, net::TcpListener};
+use std::{
+    collections::BTreeMap,
+    ffi::OsString,
+    net::{IpAddr, TcpListener},
+};
 
 use config::{Config, ConfigError, FileFormat};
 
@@ -6,6 +10,28 @@ use config::{Config, ConfigError, FileFormat};
 pub struct HttpSettings {
     pub host: String,
     pub port: u16,
+    #[serde(default)]
+    pub trusted_proxies: Vec<IpAddr>,
+    pub rate_limit: Option<RateLimitSettings>,
+    pub admin: Option<AdminSettings>,
+}
+
+/// Enables the `/admin` api for clients sending `token` as a bearer token.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct AdminSettings {
+    pub token: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct BucketSettings {
+    pub capacity: u32,
+    pub refill_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct RateLimitSettings {
+    pub per_ip: Option<BucketSettings>,
+    pub per_email: Option<BucketSettings>,
 }
 
 impl HttpSettings {
@@ -15,15 +41,313 @@ impl HttpSettings {
     }
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    #[default]
+    None,
+    Opportunistic,
+    Required,
+    Implicit,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct AcknowledgementSettings {
+    pub enabled: bool,
+    pub from: String,
+    pub subject_template: Option<String>,
+    pub text_template: Option<String>,
+    pub html_template: Option<String>,
+    pub cooldown_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpamDaemonKind {
+    Rspamd,
+    Spamd,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct SpamDaemonSettings {
+    pub kind: SpamDaemonKind,
+    pub host: String,
+    pub port: u16,
+    pub timeout_secs: u64,
+    pub tag_threshold: u32,
+    pub quarantine_threshold: u32,
+    pub reject_threshold: u32,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
 pub struct EmailSettings {
     pub smtp_host: String,
     pub smtp_port: u16,
+    #[serde(default)]
+    pub smtp_tls: SmtpTls,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_xoauth2_token: Option<String>,
+    pub smtp_ca_cert_path: Option<String>,
+    pub smtp_hello_name: Option<String>,
+    pub subject_template: Option<String>,
+    pub text_template: Option<String>,
+    pub html_template: Option<String>,
+    pub spam_subject_tag: Option<String>,
+    pub spam_daemon: Option<SpamDaemonSettings>,
     pub mailhog_host: String,
     pub mailhog_port: u16,
     pub from: String,
+    #[serde(default)]
+    pub reply_to_submitter: bool,
+    #[serde(default)]
+    pub from_submitter: bool,
     pub recipients: Vec<String>,
     pub backup_dir: String,
+    pub acknowledgement: Option<AcknowledgementSettings>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct LengthSettings {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Fields left out keep their default limits.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct ValidationSettings {
+    pub email: Option<LengthSettings>,
+    pub name: Option<LengthSettings>,
+    pub message: Option<LengthSettings>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    Text,
+    Email,
+    Phone,
+    Number,
+    Enum,
+    Boolean,
+    Url,
+}
+
+/// A custom field; lengths apply to text-like fields and `min`/`max` to numbers.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct FieldSettings {
+    pub name: String,
+    pub label: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: FieldType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub options: Vec<String>,
+    pub min_length: Option<usize>,
+    pub max_length: Option<usize>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct FormSettings {
+    pub recipients: Vec<String>,
+    pub subject_template: Option<String>,
+    #[serde(default)]
+    pub required_fields: Vec<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldSettings>,
+    pub redirect_to: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct AllowedTypeSettings {
+    pub mime: String,
+    pub extensions: Vec<String>,
+}
+
+/// Uploads are checked against the allowlist by their content, not the type the client sent.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct AttachmentSettings {
+    pub max_files: usize,
+    pub max_file_bytes: usize,
+    pub max_total_bytes: usize,
+    pub allowed_types: Vec<AllowedTypeSettings>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct BotProtectionSettings {
+    pub honeypot_field: Option<String>,
+    pub token_field: String,
+    pub token_secret: Option<String>,
+    pub min_fill_secs: u64,
+    pub max_token_age_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct CaptchaSettings {
+    pub verify_url: String,
+    pub secret: String,
+    pub response_field: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SpamRule {
+    Links {
+        max: u32,
+        points: u32,
+    },
+    Words {
+        words: Vec<String>,
+        points: u32,
+    },
+    Patterns {
+        patterns: Vec<String>,
+        points: u32,
+    },
+    Phrases {
+        phrases: Vec<String>,
+        points: u32,
+    },
+    Caps {
+        min_letters: u32,
+        max_percent: u32,
+        points: u32,
+    },
+    Scripts {
+        allowed: Vec<String>,
+        max_percent: u32,
+        points: u32,
+    },
+    EmailDomains {
+        domains: Vec<String>,
+        points: u32,
+    },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct EmailDomainSettings {
+    /// One domain per line; reloaded whenever the file changes.
+    pub disposable_list_path: Option<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(default)]
+    pub allow: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct SpamSettings {
+    pub tag_threshold: u32,
+    pub quarantine_threshold: u32,
+    #[serde(default)]
+    pub rules: Vec<SpamRule>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct OutboxSettings {
+    pub path: String,
+    pub max_attempts: u32,
+    pub retry_delay_secs: u64,
+    pub max_retry_delay_secs: u64,
+}
+
+/// An endpoint submissions are posted to, signed with `secret`. The optional `template` is a
+/// path to a Tera template rendering the json body from the default payload's fields.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct WebhookSettings {
+    pub url: String,
+    pub secret: String,
+    pub timeout_secs: u64,
+    pub max_attempts: u32,
+    pub retry_delay_secs: u64,
+    pub template: Option<String>,
+}
+
+/// An incoming webhook, as used by Slack and Discord.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct ChatWebhookSettings {
+    pub webhook_url: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct MattermostSettings {
+    pub webhook_url: String,
+    pub channel: Option<String>,
+    pub timeout_secs: u64,
+}
+
+/// Posts as the user owning `access_token`, who must have joined `room_id`.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct MatrixSettings {
+    pub homeserver_url: String,
+    pub access_token: String,
+    pub room_id: String,
+    pub timeout_secs: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, serde::Deserialize)]
+pub struct ChatSettings {
+    pub slack: Option<ChatWebhookSettings>,
+    pub discord: Option<ChatWebhookSettings>,
+    pub mattermost: Option<MattermostSettings>,
+    pub matrix: Option<MatrixSettings>,
+}
+
+/// When a submission counts as delivered, given which of its notifiers succeeded.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyPolicy {
+    #[default]
+    All,
+    Any,
+    BestEffort,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifierKind {
+    Email,
+    Webhooks,
+    Slack,
+    Discord,
+    Mattermost,
+    Matrix,
+}
+
+/// Without `notifiers`, every configured notifier is used, chat and webhooks included.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct NotifySettings {
+    #[serde(default)]
+    pub policy: NotifyPolicy,
+    pub notifiers: Option<Vec<NotifierKind>>,
+}
+
+/// A `sqlite://` or `postgres://` connection url.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct StorageSettings {
+    pub url: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadinessCheck {
+    Smtp,
+    BackupDir,
+    Outbox,
+    Storage,
+}
+
+/// Checks listed as `optional` are reported by `/ready` without making it fail.
+#[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
+pub struct ReadinessSettings {
+    pub cache_ttl_secs: u64,
+    pub timeout_secs: u64,
+    #[serde(default)]
+    pub optional: Vec<ReadinessCheck>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, serde::Deserialize)]
@@ -36,6 +360,21 @@ pub struct LogSettings {
 pub struct Settings {
     pub http: HttpSettings,
     pub email: EmailSettings,
+    #[serde(default)]
+    pub forms: BTreeMap<String, FormSettings>,
+    pub validation: Option<ValidationSettings>,
+    pub attachments: Option<AttachmentSettings>,
+    pub bot_protection: Option<BotProtectionSettings>,
+    pub captcha: Option<CaptchaSettings>,
+    pub spam: Option<SpamSettings>,
+    pub email_domains: Option<EmailDomainSettings>,
+    pub outbox: OutboxSettings,
+    pub storage: Option<StorageSettings>,
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSettings>,
+    pub chat: Option<ChatSettings>,
+    pub notify: Option<NotifySettings>,
+    pub readiness: Option<ReadinessSettings>,
     pub log: LogSettings,
 }
 
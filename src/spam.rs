@@ -0,0 +1,380 @@
+use std::convert::TryFrom;
+
+use regex::{Regex, RegexBuilder};
+use unicode_script::{Script, UnicodeScript};
+
+use super::domain::contact::Contact;
+use super::settings::{SpamRule, SpamSettings};
+
+/// Ordered by severity, so the strictest of several verdicts is their `max`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Verdict {
+    Deliver,
+    Tag(u32),
+    Quarantine(u32),
+    Reject(u32),
+}
+
+enum Rule {
+    Links {
+        max: u32,
+        points: u32,
+    },
+    Matches {
+        patterns: Vec<Regex>,
+        points: u32,
+    },
+    Caps {
+        min_letters: u32,
+        max_percent: u32,
+        points: u32,
+    },
+    Scripts {
+        allowed: Vec<String>,
+        max_percent: u32,
+        points: u32,
+    },
+    EmailDomains {
+        domains: Vec<String>,
+        points: u32,
+    },
+}
+
+fn pattern(pattern: &str) -> Regex {
+    RegexBuilder::new(pattern)
+        .case_insensitive(true)
+        .build()
+        .expect("Unable to compile spam rule pattern.")
+}
+
+/// Saturates rather than wrapping, so a flood of matches can't lower a score.
+fn count(matches: usize) -> u32 {
+    u32::try_from(matches).unwrap_or(u32::MAX)
+}
+
+fn percent(part: usize, whole: usize) -> u32 {
+    (part * 100 / whole.max(1)) as u32
+}
+
+impl Rule {
+    fn new(rule: &SpamRule) -> Self {
+        match rule {
+            SpamRule::Links { max, points } => Rule::Links {
+                max: *max,
+                points: *points,
+            },
+            SpamRule::Words { words, points } => Rule::Matches {
+                patterns: words
+                    .iter()
+                    .map(|word| pattern(&format!(r"\b{}\b", regex::escape(word))))
+                    .collect(),
+                points: *points,
+            },
+            SpamRule::Phrases { phrases, points } => Rule::Matches {
+                patterns: phrases
+                    .iter()
+                    .map(|phrase| pattern(&regex::escape(phrase)))
+                    .collect(),
+                points: *points,
+            },
+            SpamRule::Patterns { patterns, points } => Rule::Matches {
+                patterns: patterns.iter().map(|p| pattern(p)).collect(),
+                points: *points,
+            },
+            SpamRule::Caps {
+                min_letters,
+                max_percent,
+                points,
+            } => Rule::Caps {
+                min_letters: *min_letters,
+                max_percent: *max_percent,
+                points: *points,
+            },
+            SpamRule::Scripts {
+                allowed,
+                max_percent,
+                points,
+            } => Rule::Scripts {
+                allowed: allowed.iter().map(|script| script.to_lowercase()).collect(),
+                max_percent: *max_percent,
+                points: *points,
+            },
+            SpamRule::EmailDomains { domains, points } => Rule::EmailDomains {
+                domains: domains.iter().map(|domain| domain.to_lowercase()).collect(),
+                points: *points,
+            },
+        }
+    }
+
+    fn score(&self, contact: &Contact) -> u32 {
+        let message = contact.message.as_ref();
+
+        match self {
+            Rule::Links { max, points } => {
+                let links =
+                    message.matches("http://").count() + message.matches("https://").count();
+                count(links).saturating_sub(*max).saturating_mul(*points)
+            }
+            Rule::Matches { patterns, points } => {
+                let text = format!("{}\n{}", contact.name.as_ref(), message);
+                let matched = patterns.iter().filter(|p| p.is_match(&text)).count();
+                count(matched).saturating_mul(*points)
+            }
+            Rule::Caps {
+                min_letters,
+                max_percent,
+                points,
+            } => {
+                let letters: Vec<_> = message.chars().filter(|c| c.is_alphabetic()).collect();
+                let upper = letters.iter().filter(|c| c.is_uppercase()).count();
+
+                if letters.len() >= *min_letters as usize
+                    && percent(upper, letters.len()) > *max_percent
+                {
+                    *points
+                } else {
+                    0
+                }
+            }
+            Rule::Scripts {
+                allowed,
+                max_percent,
+                points,
+            } => {
+                let scripts: Vec<_> = message
+                    .chars()
+                    .map(|c| c.script())
+                    .filter(|script| !matches!(script, Script::Common | Script::Inherited))
+                    .collect();
+                let foreign = scripts
+                    .iter()
+                    .filter(|script| !allowed.contains(&script.full_name().to_lowercase()))
+                    .count();
+
+                if percent(foreign, scripts.len()) > *max_percent {
+                    *points
+                } else {
+                    0
+                }
+            }
+            Rule::EmailDomains { domains, points } => {
+                let domain = contact
+                    .email
+                    .as_ref()
+                    .rsplit('@')
+                    .next()
+                    .unwrap_or("")
+                    .to_lowercase();
+
+                let blocked = domains.iter().any(|blocked| {
+                    domain == *blocked || domain.ends_with(&format!(".{}", blocked))
+                });
+
+                if blocked {
+                    *points
+                } else {
+                    0
+                }
+            }
+        }
+    }
+}
+
+/// Scores submissions against the configured rules, delivering everything when not configured.
+pub struct SpamFilter {
+    rules: Vec<Rule>,
+    tag_threshold: u32,
+    quarantine_threshold: u32,
+}
+
+impl SpamFilter {
+    pub fn new(settings: Option<&SpamSettings>) -> Self {
+        match settings {
+            Some(settings) => Self {
+                rules: settings.rules.iter().map(Rule::new).collect(),
+                tag_threshold: settings.tag_threshold,
+                quarantine_threshold: settings.quarantine_threshold,
+            },
+            None => Self {
+                rules: Vec::new(),
+                tag_threshold: u32::MAX,
+                quarantine_threshold: u32::MAX,
+            },
+        }
+    }
+
+    fn score(&self, contact: &Contact) -> u32 {
+        self.rules.iter().fold(0u32, |score, rule| {
+            score.saturating_add(rule.score(contact))
+        })
+    }
+
+    pub fn verdict(&self, contact: &Contact) -> Verdict {
+        let score = self.score(contact);
+
+        if score >= self.quarantine_threshold {
+            Verdict::Quarantine(score)
+        } else if score >= self.tag_threshold {
+            Verdict::Tag(score)
+        } else {
+            Verdict::Deliver
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::contact::ContactPolicy;
+
+    fn contact(email: &str, message: &str) -> Contact {
+        Contact::new(email, "Shaggy", message, &ContactPolicy::default()).unwrap()
+    }
+
+    fn score(rule: SpamRule, email: &str, message: &str) -> u32 {
+        Rule::new(&rule).score(&contact(email, message))
+    }
+
+    fn filter(rules: Vec<SpamRule>) -> SpamFilter {
+        SpamFilter::new(Some(&SpamSettings {
+            tag_threshold: 5,
+            quarantine_threshold: 10,
+            rules,
+        }))
+    }
+
+    #[test]
+    fn links_beyond_the_max_score_points_each() {
+        let rule = || SpamRule::Links { max: 1, points: 2 };
+
+        assert_eq!(0, score(rule(), "scooby@mystery.van", "See https://a.fake"));
+        assert_eq!(
+            4,
+            score(
+                rule(),
+                "scooby@mystery.van",
+                "https://a.fake http://b.fake https://c.fake"
+            )
+        );
+    }
+
+    #[test]
+    fn scores_saturate_instead_of_overflowing() {
+        let rule = SpamRule::Links {
+            max: 0,
+            points: u32::MAX,
+        };
+
+        assert_eq!(
+            u32::MAX,
+            score(rule, "scooby@mystery.van", "https://a.fake https://b.fake")
+        );
+    }
+
+    #[test]
+    fn words_match_whole_words_ignoring_case() {
+        let rule = || SpamRule::Words {
+            words: vec!["casino".to_owned(), "crypto".to_owned()],
+            points: 3,
+        };
+
+        assert_eq!(6, score(rule(), "scooby@mystery.van", "CASINO and Crypto!"));
+        assert_eq!(
+            0,
+            score(rule(), "scooby@mystery.van", "cryptography casinos")
+        );
+    }
+
+    #[test]
+    fn phrases_and_patterns_match_anywhere() {
+        let phrases = SpamRule::Phrases {
+            phrases: vec!["act now".to_owned()],
+            points: 3,
+        };
+        let patterns = SpamRule::Patterns {
+            patterns: vec![r"\d{3}-\d{4}".to_owned()],
+            points: 2,
+        };
+
+        assert_eq!(3, score(phrases, "scooby@mystery.van", "Please ACT NOW."));
+        assert_eq!(2, score(patterns, "scooby@mystery.van", "Call 555-1234"));
+    }
+
+    #[test]
+    fn shouting_scores_once_there_are_enough_letters() {
+        let rule = || SpamRule::Caps {
+            min_letters: 10,
+            max_percent: 70,
+            points: 3,
+        };
+
+        assert_eq!(0, score(rule(), "scooby@mystery.van", "ZOINKS!"));
+        assert_eq!(3, score(rule(), "scooby@mystery.van", "ZOINKS SCOOB, RUN!"));
+        assert_eq!(0, score(rule(), "scooby@mystery.van", "Zoinks Scoob, run!"));
+    }
+
+    #[test]
+    fn scripts_outside_the_allowed_ones_score() {
+        let rule = || SpamRule::Scripts {
+            allowed: vec!["Latin".to_owned()],
+            max_percent: 20,
+            points: 4,
+        };
+
+        assert_eq!(0, score(rule(), "scooby@mystery.van", "Zoinks, 123!"));
+        assert_eq!(4, score(rule(), "scooby@mystery.van", "Привет, друг"));
+    }
+
+    #[test]
+    fn blocked_email_domains_include_subdomains() {
+        let rule = || SpamRule::EmailDomains {
+            domains: vec!["spam.fake".to_owned()],
+            points: 10,
+        };
+
+        assert_eq!(10, score(rule(), "bot@Spam.Fake", "Hi"));
+        assert_eq!(10, score(rule(), "bot@mail.spam.fake", "Hi"));
+        assert_eq!(0, score(rule(), "bot@notspam.fake", "Hi"));
+    }
+
+    #[test]
+    fn thresholds_decide_the_verdict() {
+        let words = |points| SpamRule::Words {
+            words: vec!["casino".to_owned()],
+            points,
+        };
+
+        assert_eq!(
+            Verdict::Deliver,
+            filter(vec![words(4)]).verdict(&contact("scooby@mystery.van", "casino"))
+        );
+        assert_eq!(
+            Verdict::Tag(5),
+            filter(vec![words(5)]).verdict(&contact("scooby@mystery.van", "casino"))
+        );
+        assert_eq!(
+            Verdict::Quarantine(10),
+            filter(vec![words(5), words(5)]).verdict(&contact("scooby@mystery.van", "casino"))
+        );
+    }
+
+    #[test]
+    fn the_strictest_verdict_wins() {
+        assert_eq!(
+            Verdict::Quarantine(1),
+            Verdict::Tag(9).max(Verdict::Quarantine(1))
+        );
+        assert_eq!(Verdict::Tag(9), Verdict::Tag(9).max(Verdict::Tag(5)));
+        assert_eq!(Verdict::Tag(5), Verdict::Deliver.max(Verdict::Tag(5)));
+    }
+
+    #[test]
+    fn disabled_filter_delivers_everything() {
+        let filter = SpamFilter::new(None);
+
+        assert_eq!(
+            Verdict::Deliver,
+            filter.verdict(&contact("bot@spam.fake", "CASINO CRYPTO ACT NOW"))
+        );
+    }
+}
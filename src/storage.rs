@@ -0,0 +1,464 @@
+use std::collections::BTreeMap;
+
+use chrono::{TimeZone, Utc};
+use sqlx::any::AnyPoolOptions;
+use sqlx::migrate::Migrator;
+use sqlx::AnyPool;
+
+use super::domain::contact::Contact;
+use super::domain::metadata::Metadata;
+use super::settings::StorageSettings;
+use super::spam::Verdict;
+
+static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/storage/sqlite");
+static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/storage/postgres");
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    Pending,
+    Sent,
+    Dead,
+    Quarantined,
+    Rejected,
+}
+
+impl Status {
+    fn as_str(self) -> &'static str {
+        match self {
+            Status::Pending => "pending",
+            Status::Sent => "sent",
+            Status::Dead => "dead",
+            Status::Quarantined => "quarantined",
+            Status::Rejected => "rejected",
+        }
+    }
+}
+
+fn verdict(verdict: Verdict) -> (&'static str, Option<i64>) {
+    match verdict {
+        Verdict::Deliver => ("deliver", None),
+        Verdict::Tag(score) => ("tag", Some(i64::from(score))),
+        Verdict::Quarantine(score) => ("quarantine", Some(i64::from(score))),
+        Verdict::Reject(score) => ("reject", Some(i64::from(score))),
+    }
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct Row {
+    id: i64,
+    created_at: i64,
+    form_id: Option<String>,
+    email: String,
+    name: String,
+    message: String,
+    fields: String,
+    client_ip: Option<String>,
+    user_agent: Option<String>,
+    referer: Option<String>,
+    status: String,
+    spam_verdict: String,
+    spam_score: Option<i64>,
+}
+
+const COLUMNS: &str = "id, created_at, form_id, email, name, message, fields, client_ip, \
+                       user_agent, referer, status, spam_verdict, spam_score";
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize)]
+pub struct Submission {
+    pub id: i64,
+    pub submitted_at: String,
+    pub form_id: Option<String>,
+    pub email: String,
+    pub name: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+    pub status: String,
+    pub spam_verdict: String,
+    pub spam_score: Option<i64>,
+}
+
+impl From<Row> for Submission {
+    fn from(row: Row) -> Self {
+        Self {
+            id: row.id,
+            submitted_at: Utc
+                .timestamp_millis_opt(row.created_at)
+                .single()
+                .map(|at| at.to_rfc3339())
+                .unwrap_or_default(),
+            form_id: row.form_id,
+            email: row.email,
+            name: row.name,
+            message: row.message,
+            fields: serde_json::from_str(&row.fields).unwrap_or_default(),
+            client_ip: row.client_ip,
+            user_agent: row.user_agent,
+            referer: row.referer,
+            status: row.status,
+            spam_verdict: row.spam_verdict,
+            spam_score: row.spam_score,
+        }
+    }
+}
+
+/// Narrows a listing, with timestamps in unix milliseconds.
+#[derive(Debug, Default)]
+pub struct Filter {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub form_id: Option<String>,
+    pub email: Option<String>,
+    pub status: Option<String>,
+    pub spam: Option<bool>,
+}
+
+enum Param {
+    Integer(i64),
+    Text(String),
+}
+
+impl Filter {
+    /// Builds the where clause with numbered placeholders, which both SQLite and Postgres accept.
+    fn clause(&self) -> (String, Vec<Param>) {
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        let mut push = |condition: &str, param| {
+            params.push(param);
+            conditions.push(format!("{} ${}", condition, params.len()));
+        };
+
+        if let Some(from) = self.from {
+            push("created_at >=", Param::Integer(from));
+        }
+        if let Some(to) = self.to {
+            push("created_at <=", Param::Integer(to));
+        }
+        if let Some(form_id) = &self.form_id {
+            push("form_id =", Param::Text(form_id.to_owned()));
+        }
+        if let Some(email) = &self.email {
+            push("LOWER(email) =", Param::Text(email.to_lowercase()));
+        }
+        if let Some(status) = &self.status {
+            push("status =", Param::Text(status.to_owned()));
+        }
+        match self.spam {
+            Some(true) => conditions.push("spam_verdict <> 'deliver'".to_owned()),
+            Some(false) => conditions.push("spam_verdict = 'deliver'".to_owned()),
+            None => {}
+        }
+
+        if conditions.is_empty() {
+            (String::new(), params)
+        } else {
+            (format!(" WHERE {}", conditions.join(" AND ")), params)
+        }
+    }
+}
+
+/// Keeps a queryable record of every validated submission, in SQLite or Postgres.
+#[derive(Clone)]
+pub struct Storage {
+    pool: AnyPool,
+}
+
+impl Storage {
+    pub async fn connect(settings: &StorageSettings) -> Result<Self, sqlx::Error> {
+        sqlx::any::install_default_drivers();
+
+        let pool = AnyPoolOptions::new().connect(&settings.url).await?;
+
+        let migrator = if settings.url.starts_with("postgres") {
+            &POSTGRES_MIGRATOR
+        } else {
+            &SQLITE_MIGRATOR
+        };
+        migrator.run(&pool).await?;
+
+        Ok(Self { pool })
+    }
+
+    pub async fn ping(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("SELECT 1").execute(&self.pool).await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(name = "Store submission", skip(self, contact, metadata))]
+    pub async fn insert(
+        &self,
+        contact: &Contact,
+        metadata: &Metadata,
+        spam: Verdict,
+        status: Status,
+    ) -> Result<i64, sqlx::Error> {
+        let (spam_verdict, spam_score) = verdict(spam);
+
+        let id = sqlx::query_scalar(
+            "INSERT INTO submissions
+             (created_at, form_id, email, name, message, fields, client_ip, user_agent, referer,
+              status, spam_verdict, spam_score)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             RETURNING id",
+        )
+        .bind(metadata.submitted_at.timestamp_millis())
+        .bind(&metadata.form_id)
+        .bind(contact.email.as_ref())
+        .bind(contact.name.as_ref())
+        .bind(contact.message.as_ref())
+        .bind(serde_json::to_string(&contact.fields).expect("Fields are always serializable."))
+        .bind(&metadata.client_ip)
+        .bind(&metadata.user_agent)
+        .bind(&metadata.referer)
+        .bind(status.as_str())
+        .bind(spam_verdict)
+        .bind(spam_score)
+        .fetch_one(&self.pool)
+        .await?;
+
+        tracing::info!("Submission stored as {}.", id);
+
+        Ok(id)
+    }
+
+    pub async fn set_status(&self, id: i64, status: Status) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE submissions SET status = $1 WHERE id = $2")
+            .bind(status.as_str())
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Lists the newest submissions first, along with how many match in total.
+    pub async fn list(
+        &self,
+        filter: &Filter,
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<Submission>, i64), sqlx::Error> {
+        let (clause, params) = filter.clause();
+
+        let count = format!("SELECT COUNT(*) FROM submissions{}", clause);
+        let mut count = sqlx::query_scalar(&count);
+        let rows = format!(
+            "SELECT {} FROM submissions{} ORDER BY created_at DESC, id DESC LIMIT ${} OFFSET ${}",
+            COLUMNS,
+            clause,
+            params.len() + 1,
+            params.len() + 2
+        );
+        let mut rows = sqlx::query_as::<_, Row>(&rows);
+
+        for param in &params {
+            match param {
+                Param::Integer(value) => {
+                    count = count.bind(*value);
+                    rows = rows.bind(*value);
+                }
+                Param::Text(value) => {
+                    count = count.bind(value.as_str());
+                    rows = rows.bind(value.as_str());
+                }
+            }
+        }
+
+        let total = count.fetch_one(&self.pool).await?;
+        let rows = rows.bind(limit).bind(offset).fetch_all(&self.pool).await?;
+
+        Ok((rows.into_iter().map(Submission::from).collect(), total))
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<Submission>, sqlx::Error> {
+        let row: Option<Row> = sqlx::query_as(&format!(
+            "SELECT {} FROM submissions WHERE id = $1",
+            COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(Submission::from))
+    }
+
+    /// Returns whether there was a submission to delete.
+    pub async fn delete(&self, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM submissions WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::contact::ContactPolicy;
+    use crate::fixtures::{contact, metadata};
+
+    async fn storage() -> Storage {
+        let path = std::env::temp_dir().join(format!("submissions-{}.db", uuid::Uuid::new_v4()));
+
+        Storage::connect(&StorageSettings {
+            url: format!("sqlite://{}?mode=rwc", path.to_string_lossy()),
+        })
+        .await
+        .expect("Unable to open storage.")
+    }
+
+    async fn row(storage: &Storage, id: i64) -> (i64, Option<String>, String, String, Option<i64>) {
+        sqlx::query_as(
+            "SELECT created_at, form_id, status, spam_verdict, spam_score
+             FROM submissions WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_one(&storage.pool)
+        .await
+        .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn submissions_are_stored_with_their_metadata_and_verdict() {
+        let storage = storage().await;
+
+        let first = storage
+            .insert(&contact(), &metadata(), Verdict::Deliver, Status::Pending)
+            .await
+            .unwrap();
+        let second = storage
+            .insert(
+                &contact(),
+                &metadata(),
+                Verdict::Quarantine(12),
+                Status::Quarantined,
+            )
+            .await
+            .unwrap();
+
+        assert_ne!(first, second);
+        assert_eq!(
+            (
+                1_622_505_600_000,
+                Some("support".to_owned()),
+                "pending".to_owned(),
+                "deliver".to_owned(),
+                None
+            ),
+            row(&storage, first).await
+        );
+        assert_eq!(
+            (
+                1_622_505_600_000,
+                Some("support".to_owned()),
+                "quarantined".to_owned(),
+                "quarantine".to_owned(),
+                Some(12)
+            ),
+            row(&storage, second).await
+        );
+    }
+
+    #[actix_rt::test]
+    async fn delivery_status_can_be_updated() {
+        let storage = storage().await;
+
+        let id = storage
+            .insert(&contact(), &metadata(), Verdict::Deliver, Status::Pending)
+            .await
+            .unwrap();
+        storage.set_status(id, Status::Sent).await.unwrap();
+
+        assert_eq!("sent", row(&storage, id).await.2);
+    }
+
+    #[actix_rt::test]
+    async fn submissions_can_be_listed_with_filters() {
+        let storage = storage().await;
+        let later = Metadata {
+            submitted_at: Utc.timestamp_millis_opt(1_622_592_000_000).unwrap(),
+            form_id: None,
+            ..metadata()
+        };
+        let velma = Contact::new(
+            "Velma@Mystery.Van",
+            "Velma",
+            "Jinkies!",
+            &ContactPolicy::default(),
+        )
+        .unwrap();
+
+        let first = storage
+            .insert(&contact(), &metadata(), Verdict::Deliver, Status::Pending)
+            .await
+            .unwrap();
+        let second = storage
+            .insert(&velma, &later, Verdict::Tag(7), Status::Sent)
+            .await
+            .unwrap();
+
+        let ids = |(submissions, total): (Vec<Submission>, i64)| {
+            let ids: Vec<i64> = submissions.iter().map(|submission| submission.id).collect();
+            (ids, total)
+        };
+        let storage = &storage;
+        let list = |filter| async move { ids(storage.list(&filter, 10, 0).await.unwrap()) };
+
+        assert_eq!((vec![second, first], 2), list(Filter::default()).await);
+        assert_eq!(
+            (vec![second], 2),
+            ids(storage.list(&Filter::default(), 1, 0).await.unwrap())
+        );
+        assert_eq!(
+            (vec![first], 1),
+            list(Filter {
+                to: Some(1_622_505_600_000),
+                ..Filter::default()
+            })
+            .await
+        );
+        assert_eq!(
+            (vec![second], 1),
+            list(Filter {
+                from: Some(1_622_505_600_001),
+                email: Some("velma@mystery.van".to_owned()),
+                status: Some("sent".to_owned()),
+                spam: Some(true),
+                ..Filter::default()
+            })
+            .await
+        );
+        assert_eq!(
+            (vec![first], 1),
+            list(Filter {
+                form_id: Some("support".to_owned()),
+                spam: Some(false),
+                ..Filter::default()
+            })
+            .await
+        );
+    }
+
+    #[actix_rt::test]
+    async fn submissions_can_be_fetched_and_deleted() {
+        let storage = storage().await;
+
+        let id = storage
+            .insert(&contact(), &metadata(), Verdict::Deliver, Status::Pending)
+            .await
+            .unwrap();
+
+        let submission = storage.get(id).await.unwrap().unwrap();
+        assert_eq!("scooby@mystery.van", submission.email);
+        assert_eq!("2021-06-01T00:00:00+00:00", submission.submitted_at);
+
+        assert!(storage.delete(id).await.unwrap());
+        assert!(!storage.delete(id).await.unwrap());
+        assert_eq!(None, storage.get(id).await.unwrap());
+    }
+}
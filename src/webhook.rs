@@ -0,0 +1,322 @@
+use std::error::Error;
+use std::time::Duration;
+
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use tera::Tera;
+
+use super::domain::contact::Contact;
+use super::domain::metadata::Metadata;
+use super::notifier::{delivery_key, Notifier};
+use super::settings::WebhookSettings;
+
+type HmacSha256 = Hmac<Sha256>;
+
+pub const SIGNATURE_HEADER: &str = "X-Contact-Signature";
+/// Carries the delivery key, so receivers can discard a retry they already processed.
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+/// Caps the backoff between an endpoint's attempts, so one slow endpoint holds up the outbox
+/// only briefly before the outbox's own retries take over.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(serde::Serialize, Debug)]
+struct AttachmentPayload<'a> {
+    filename: &'a str,
+    content_type: &'a str,
+    size: usize,
+}
+
+/// The json posted to every endpoint, and the context of their templates.
+#[derive(serde::Serialize, Debug)]
+struct Payload<'a> {
+    name: &'a str,
+    email: &'a str,
+    message: &'a str,
+    fields: &'a std::collections::BTreeMap<String, String>,
+    attachments: Vec<AttachmentPayload<'a>>,
+    form: Option<&'a str>,
+    timestamp: String,
+    client_ip: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    referer: Option<&'a str>,
+    spam_score: Option<u32>,
+}
+
+impl<'a> Payload<'a> {
+    fn new(contact: &'a Contact, metadata: &'a Metadata) -> Self {
+        Self {
+            name: contact.name.as_ref(),
+            email: contact.email.as_ref(),
+            message: contact.message.as_ref(),
+            fields: &contact.fields,
+            attachments: contact
+                .attachments
+                .iter()
+                .map(|attachment| AttachmentPayload {
+                    filename: &attachment.filename,
+                    content_type: &attachment.content_type,
+                    size: attachment.content.len(),
+                })
+                .collect(),
+            form: metadata.form_id.as_deref(),
+            timestamp: metadata.submitted_at.to_rfc3339(),
+            client_ip: metadata.client_ip.as_deref(),
+            user_agent: metadata.user_agent.as_deref(),
+            referer: metadata.referer.as_deref(),
+            spam_score: metadata.spam_score,
+        }
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the body, prefixed like GitHub's `sha256=` signatures.
+fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("Hmac accepts keys of any size.");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+struct Endpoint {
+    url: String,
+    secret: String,
+    timeout: Duration,
+    max_attempts: u32,
+    retry_delay: Duration,
+    /// Renders json reshaping the payload, with the payload's fields as its context.
+    template: Option<Tera>,
+}
+
+impl Endpoint {
+    fn new(settings: &WebhookSettings) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let template = match &settings.template {
+            Some(path) => {
+                let mut tera = Tera::default();
+                tera.add_raw_template(&settings.url, &std::fs::read_to_string(path)?)?;
+                Some(tera)
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            url: settings.url.to_owned(),
+            secret: settings.secret.to_owned(),
+            timeout: Duration::from_secs(settings.timeout_secs),
+            max_attempts: settings.max_attempts.max(1),
+            retry_delay: Duration::from_secs(settings.retry_delay_secs),
+            template,
+        })
+    }
+
+    fn body(&self, payload: &Payload) -> Result<Vec<u8>, Box<dyn Error>> {
+        match &self.template {
+            Some(tera) => {
+                let context = tera::Context::from_serialize(payload)?;
+                let rendered = tera.render(&self.url, &context)?;
+                let json: serde_json::Value = serde_json::from_str(&rendered)?;
+                Ok(serde_json::to_vec(&json)?)
+            }
+            None => Ok(serde_json::to_vec(payload)?),
+        }
+    }
+
+    async fn post(
+        &self,
+        client: &reqwest::Client,
+        key: &str,
+        body: Vec<u8>,
+    ) -> Result<(), reqwest::Error> {
+        client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(IDEMPOTENCY_HEADER, key)
+            .header(SIGNATURE_HEADER, signature(&self.secret, &body))
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(())
+    }
+
+    /// Retries up to `max_attempts` times, backing off exponentially up to `MAX_RETRY_DELAY`.
+    #[tracing::instrument(name = "Deliver webhook", skip(self, client, body), fields(url = %self.url))]
+    async fn deliver(
+        &self,
+        client: &reqwest::Client,
+        key: &str,
+        body: Vec<u8>,
+    ) -> Result<(), reqwest::Error> {
+        let mut attempt = 1;
+
+        loop {
+            match self.post(client, key, body.clone()).await {
+                Ok(()) => {
+                    tracing::info!("Webhook delivered.");
+                    return Ok(());
+                }
+                Err(error) if attempt >= self.max_attempts => return Err(error),
+                Err(error) => {
+                    tracing::warn!("Webhook failed, will retry: {}", error);
+                    let delay = self
+                        .retry_delay
+                        .checked_mul(2u32.saturating_pow(attempt - 1))
+                        .map_or(MAX_RETRY_DELAY, |delay| delay.min(MAX_RETRY_DELAY));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Posts submissions as signed json to one configured endpoint.
+pub struct Webhook {
+    client: reqwest::Client,
+    endpoint: Endpoint,
+    name: String,
+}
+
+impl Webhook {
+    /// A notifier per endpoint, so each endpoint's deliveries are tracked and retried on their
+    /// own. Fails when an endpoint's template can't be read or parsed.
+    pub fn all(settings: &[WebhookSettings]) -> Result<Vec<Self>, Box<dyn Error + Send + Sync>> {
+        let client = reqwest::Client::new();
+
+        settings
+            .iter()
+            .map(|settings| {
+                let endpoint = Endpoint::new(settings).map_err(|error| {
+                    format!(
+                        "Unable to load webhook template for {}: {}",
+                        settings.url, error
+                    )
+                })?;
+
+                Ok(Self {
+                    client: client.clone(),
+                    endpoint,
+                    name: format!("webhook:{}", settings.url),
+                })
+            })
+            .collect()
+    }
+
+    #[tracing::instrument(name = "Send contact webhook", skip(self, contact, metadata))]
+    async fn send(
+        &self,
+        id: i64,
+        contact: &Contact,
+        metadata: &Metadata,
+    ) -> Result<(), Box<dyn Error>> {
+        let body = self.endpoint.body(&Payload::new(contact, metadata))?;
+        self.endpoint
+            .deliver(&self.client, &delivery_key(id, metadata), body)
+            .await?;
+
+        Ok(())
+    }
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn notify<'a>(
+        &'a self,
+        id: i64,
+        contact: &'a Contact,
+        metadata: &'a Metadata,
+    ) -> LocalBoxFuture<'a, Result<(), Box<dyn Error>>> {
+        self.send(id, contact, metadata).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{contact, metadata};
+
+    fn endpoint(template: Option<&str>) -> Endpoint {
+        Endpoint {
+            url: "http://localhost/hook".to_owned(),
+            secret: "scooby-snack".to_owned(),
+            timeout: Duration::from_secs(1),
+            max_attempts: 1,
+            retry_delay: Duration::from_secs(0),
+            template: template.map(|template| {
+                let mut tera = Tera::default();
+                tera.add_raw_template("http://localhost/hook", template)
+                    .unwrap();
+                tera
+            }),
+        }
+    }
+
+    #[test]
+    fn signs_the_body_with_hmac_sha256() {
+        assert_eq!(
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8",
+            signature("key", b"The quick brown fox jumps over the lazy dog")
+        );
+    }
+
+    #[test]
+    fn posts_the_contact_and_metadata_by_default() {
+        let (contact, metadata) = (contact(), metadata());
+        let body = endpoint(None)
+            .body(&Payload::new(&contact, &metadata))
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!("Shaggy", json["name"]);
+        assert_eq!("scooby@mystery.van", json["email"]);
+        assert_eq!("Zoinks!", json["message"]);
+        assert_eq!("support", json["form"]);
+        assert_eq!("2021-06-01T00:00:00+00:00", json["timestamp"]);
+    }
+
+    #[test]
+    fn templates_reshape_the_payload() {
+        let (contact, metadata) = (contact(), metadata());
+        let template = r#"{"contact": {"full_name": {{ name | json_encode() }}}, "source": {{ form | json_encode() }}}"#;
+
+        let body = endpoint(Some(template))
+            .body(&Payload::new(&contact, &metadata))
+            .unwrap();
+
+        assert_eq!(
+            serde_json::json!({"contact": {"full_name": "Shaggy"}, "source": "support"}),
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        );
+    }
+
+    #[test]
+    fn templates_must_render_json() {
+        let (contact, metadata) = (contact(), metadata());
+
+        assert!(endpoint(Some("name={{ name }}"))
+            .body(&Payload::new(&contact, &metadata))
+            .is_err());
+    }
+
+    #[test]
+    fn missing_templates_fail_to_load() {
+        let error = Webhook::all(&[WebhookSettings {
+            url: "http://localhost/hook".to_owned(),
+            secret: "scooby-snack".to_owned(),
+            timeout_secs: 1,
+            max_attempts: 1,
+            retry_delay_secs: 0,
+            template: Some("/nonexistent/mystery.json".to_owned()),
+        }])
+        .err()
+        .unwrap();
+
+        assert!(error
+            .to_string()
+            .starts_with("Unable to load webhook template for http://localhost/hook"));
+    }
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::{spawn_app, spawn_app_with};
+use contact_api::settings::AdminSettings;
+
+const TOKEN: &str = "mystery-machine";
+
+async fn spawn_admin_app() -> common::TestApp {
+    spawn_app_with(|settings| {
+        settings.http.admin = Some(AdminSettings {
+            token: TOKEN.to_owned(),
+        })
+    })
+    .await
+}
+
+async fn submit(app: &common::TestApp, name: &str, email: &str) {
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[("name", name), ("email", email), ("message", "Jinkies!")])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+}
+
+fn get(app: &common::TestApp, path: &str) -> reqwest::RequestBuilder {
+    reqwest::Client::new()
+        .get(format!("{}/admin{}", &app.address, path))
+        .bearer_auth(TOKEN)
+}
+
+#[actix_rt::test]
+async fn admin_api_requires_the_token() {
+    let app = spawn_admin_app().await;
+
+    let missing = reqwest::Client::new()
+        .get(format!("{}/admin/submissions", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    let wrong = reqwest::Client::new()
+        .get(format!("{}/admin/submissions", &app.address))
+        .bearer_auth("scooby-snack")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::UNAUTHORIZED, missing.status());
+    assert_eq!(reqwest::StatusCode::UNAUTHORIZED, wrong.status());
+}
+
+#[actix_rt::test]
+async fn admin_api_is_disabled_by_default() {
+    let app = spawn_app().await;
+
+    let response = get(&app, "/submissions")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status());
+}
+
+#[actix_rt::test]
+async fn submissions_can_be_listed_and_filtered() {
+    let app = spawn_admin_app().await;
+
+    submit(&app, "Velma", "velma@mystery.van").await;
+    submit(&app, "Fred", "fred@mystery.van").await;
+
+    let page: serde_json::Value = get(&app, "/submissions?per_page=1")
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(2, page["total"]);
+    assert_eq!(1, page["per_page"]);
+    assert_eq!("fred@mystery.van", page["submissions"][0]["email"]);
+
+    let page: serde_json::Value = get(&app, "/submissions?email=VELMA@mystery.van&spam=false")
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .unwrap();
+
+    assert_eq!(1, page["total"]);
+    assert_eq!("Velma", page["submissions"][0]["name"]);
+
+    let response = get(&app, "/submissions?from=yesterday")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+}
+
+#[actix_rt::test]
+async fn submissions_can_be_fetched_and_deleted() {
+    let app = spawn_admin_app().await;
+
+    submit(&app, "Daphne", "daphne@mystery.van").await;
+
+    let page: serde_json::Value = get(&app, "/submissions")
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .unwrap();
+    let id = page["submissions"][0]["id"].as_i64().unwrap();
+
+    let submission: serde_json::Value = get(&app, &format!("/submissions/{}", id))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json()
+        .await
+        .unwrap();
+    assert_eq!("daphne@mystery.van", submission["email"]);
+    assert_eq!("Jinkies!", submission["message"]);
+
+    let deleted = reqwest::Client::new()
+        .delete(format!("{}/admin/submissions/{}", &app.address, id))
+        .bearer_auth(TOKEN)
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::NO_CONTENT, deleted.status());
+
+    let missing = get(&app, &format!("/submissions/{}", id))
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, missing.status());
+}
@@ -0,0 +1,136 @@
+mod common;
+
+use common::{spawn_app, spawn_app_with};
+use std::collections::HashMap;
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\0\0\0\x01\0\0\0\x01";
+const PDF: &[u8] = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n";
+
+fn form() -> reqwest::multipart::Form {
+    reqwest::multipart::Form::new()
+        .text("name", "Velma")
+        .text("email", "velma@mystery.van")
+        .text("message", "I found a clue!")
+}
+
+fn file(filename: &str, content: &[u8]) -> reqwest::multipart::Part {
+    reqwest::multipart::Part::bytes(content.to_vec()).file_name(filename.to_owned())
+}
+
+async fn submit(app: &common::TestApp, form: reqwest::multipart::Form) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .multipart(form)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn attachments_error(response: reqwest::Response) -> Option<String> {
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let mut errors: HashMap<String, Option<String>> = response.json().await.unwrap();
+    errors.remove("attachments").flatten()
+}
+
+#[actix_rt::test]
+async fn allowed_attachments_are_attached_and_backed_up() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, form().part("clue", file("clue.png", PNG))).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let backup_dir = std::path::Path::new(&app.email_settings.backup_dir);
+    let email = std::fs::read_dir(backup_dir)
+        .expect("Unable to read backup dir.")
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension() == Some("eml".as_ref()))
+        .expect("There should of been one backup.");
+    let content = std::fs::read_to_string(&email).unwrap();
+
+    assert!(content.contains("multipart/mixed"));
+    assert!(content.contains("image/png"));
+    assert!(content.contains("filename=\"clue.png\""));
+
+    let saved = backup_dir.join(email.file_stem().unwrap()).join("clue.png");
+    assert_eq!(PNG, &std::fs::read(saved).unwrap()[..]);
+}
+
+#[actix_rt::test]
+async fn attachments_are_sniffed_rather_than_trusting_the_filename() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, form().part("clue", file("clue.png", PDF))).await;
+
+    assert_eq!(
+        Some("Attachment type is not allowed.".to_owned()),
+        attachments_error(response).await
+    );
+}
+
+#[actix_rt::test]
+async fn too_many_attachments_return_a_400() {
+    let app = spawn_app_with(|settings| {
+        if let Some(attachments) = settings.attachments.as_mut() {
+            attachments.max_files = 1;
+        }
+    })
+    .await;
+
+    let form = form()
+        .part("first", file("first.png", PNG))
+        .part("second", file("second.png", PNG));
+    let response = submit(&app, form).await;
+
+    assert_eq!(
+        Some("No more than 1 attachments may be sent.".to_owned()),
+        attachments_error(response).await
+    );
+}
+
+#[actix_rt::test]
+async fn attachments_are_rejected_when_not_configured() {
+    let app = spawn_app_with(|settings| settings.attachments = None).await;
+
+    let response = submit(&app, form().part("clue", file("clue.png", PNG))).await;
+
+    assert_eq!(
+        Some("Attachments are not accepted.".to_owned()),
+        attachments_error(response).await
+    );
+}
+
+#[actix_rt::test]
+async fn reading_stops_once_attachments_exceed_the_total() {
+    let app = spawn_app_with(|settings| {
+        if let Some(attachments) = settings.attachments.as_mut() {
+            attachments.max_files = 1;
+            attachments.max_total_bytes = 64;
+        }
+    })
+    .await;
+
+    let form = form()
+        .part("first", file("first.png", PNG))
+        .part("second", file("second.png", &[0; 4096]))
+        .part("third", file("third.png", &[0; 4096]));
+    let response = submit(&app, form).await;
+
+    assert_eq!(
+        Some("Attachments may not be larger than 64 bytes in total.".to_owned()),
+        attachments_error(response).await
+    );
+}
+
+#[actix_rt::test]
+async fn empty_attachments_return_a_400() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, form().part("clue", file("clue.png", b""))).await;
+
+    assert_eq!(
+        Some("Attachments may not be empty.".to_owned()),
+        attachments_error(response).await
+    );
+}
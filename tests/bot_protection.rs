@@ -0,0 +1,149 @@
+mod common;
+
+use common::{spawn_app, spawn_app_with, TestApp};
+
+#[derive(serde::Deserialize)]
+struct TokenResponse {
+    token: String,
+}
+
+async fn submit(app: &TestApp, extra: &[(&str, &str)]) -> reqwest::Response {
+    let mut params = vec![
+        ("name", "Shaggy"),
+        ("email", "scooby@mystery.van"),
+        ("message", "Zoinks!"),
+    ];
+    params.extend_from_slice(extra);
+
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&params)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn token(app: &TestApp) -> String {
+    reqwest::Client::new()
+        .get(format!("{}/token", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .json::<TokenResponse>()
+        .await
+        .expect("Unable to parse token.")
+        .token
+}
+
+fn backups(app: &TestApp) -> usize {
+    std::fs::read_dir(&app.email_settings.backup_dir)
+        .expect("Unable to read backup dir.")
+        .count()
+}
+
+async fn spawn_app_with_tokens(min_fill_secs: u64) -> TestApp {
+    spawn_app_with(|settings| {
+        let protection = settings.bot_protection.as_mut().unwrap();
+        protection.token_secret = Some("jinkies".to_owned());
+        protection.min_fill_secs = min_fill_secs;
+    })
+    .await
+}
+
+#[actix_rt::test]
+async fn filled_honeypot_is_dropped_silently() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, &[("website", "http://spam.example")]).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert_eq!(0, backups(&app));
+}
+
+#[actix_rt::test]
+async fn dropped_form_submissions_are_redirected_like_real_ones() {
+    let app = spawn_app_with(|settings| {
+        let form = settings.forms.get_mut("support").unwrap();
+        form.redirect_to = Some("https://mystery.van/thanks".to_owned());
+    })
+    .await;
+
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .post(format!("{}/forms/support", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("website", "http://spam.example"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::SEE_OTHER, response.status());
+    assert_eq!(
+        "https://mystery.van/thanks",
+        response.headers().get("Location").unwrap()
+    );
+    assert_eq!(0, backups(&app));
+}
+
+#[actix_rt::test]
+async fn empty_honeypot_is_accepted() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, &[("website", "")]).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert_eq!(1, backups(&app));
+}
+
+#[actix_rt::test]
+async fn token_endpoint_is_missing_without_a_secret() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .get(format!("{}/token", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status());
+}
+
+#[actix_rt::test]
+async fn signed_token_is_accepted() {
+    let app = spawn_app_with_tokens(0).await;
+
+    let token = token(&app).await;
+    let response = submit(&app, &[("token", &token)]).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert_eq!(1, backups(&app));
+}
+
+#[actix_rt::test]
+async fn missing_or_forged_token_is_dropped_silently() {
+    let app = spawn_app_with_tokens(0).await;
+
+    let missing = submit(&app, &[]).await;
+    let forged = submit(&app, &[("token", "1622505600.deadbeef")]).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, missing.status());
+    assert_eq!(reqwest::StatusCode::ACCEPTED, forged.status());
+    assert_eq!(0, backups(&app));
+}
+
+#[actix_rt::test]
+async fn submission_faster_than_min_fill_time_is_dropped_silently() {
+    let app = spawn_app_with_tokens(60).await;
+
+    let token = token(&app).await;
+    let response = submit(&app, &[("token", &token)]).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert_eq!(0, backups(&app));
+}
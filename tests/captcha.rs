@@ -0,0 +1,134 @@
+mod common;
+
+use std::collections::HashMap;
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use contact_api::settings::CaptchaSettings;
+
+use common::{spawn_app_with, TestApp};
+
+const SECRET: &str = "jinkies";
+const VALID_RESPONSE: &str = "mystery-solved";
+
+async fn verify(form: web::Form<HashMap<String, String>>) -> HttpResponse {
+    let success = form.get("secret").map(String::as_str) == Some(SECRET)
+        && form.get("response").map(String::as_str) == Some(VALID_RESPONSE)
+        && form.get("remoteip").map(String::as_str) == Some("127.0.0.1");
+
+    HttpResponse::Ok().json(serde_json::json!({
+        "success": success,
+        "error-codes": if success { vec![] } else { vec!["invalid-input-response"] },
+    }))
+}
+
+fn spawn_verify_stub() -> String {
+    let server = HttpServer::new(|| App::new().route("/siteverify", web::post().to(verify)))
+        .bind("127.0.0.1:0")
+        .expect("Unable to bind captcha stub.");
+    let port = server.addrs()[0].port();
+
+    tokio::spawn(server.run());
+
+    format!("http://127.0.0.1:{}/siteverify", port)
+}
+
+async fn spawn_app_with_captcha(verify_url: String) -> TestApp {
+    spawn_app_with(|settings| {
+        settings.captcha = Some(CaptchaSettings {
+            verify_url,
+            secret: SECRET.to_owned(),
+            response_field: "h-captcha-response".to_owned(),
+            timeout_secs: 1,
+        });
+    })
+    .await
+}
+
+async fn submit(app: &TestApp, captcha: Option<&str>) -> reqwest::Response {
+    let mut params = vec![
+        ("name", ""),
+        ("email", "scooby@mystery.van"),
+        ("message", "Zoinks!"),
+    ];
+    if let Some(captcha) = captcha {
+        params.push(("h-captcha-response", captcha));
+    }
+
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&params)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+async fn errors(response: reqwest::Response) -> HashMap<String, Option<String>> {
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    response.json().await.expect("Unable to parse errors.")
+}
+
+#[actix_rt::test]
+async fn verified_captcha_goes_on_to_validate_the_contact() {
+    let app = spawn_app_with_captcha(spawn_verify_stub()).await;
+
+    let errors = errors(submit(&app, Some(VALID_RESPONSE)).await).await;
+
+    assert_eq!(
+        Some(&Some("Name may not be empty.".to_owned())),
+        errors.get("name")
+    );
+    assert_eq!(None, errors.get("captcha"));
+}
+
+#[actix_rt::test]
+async fn missing_captcha_returns_a_captcha_error() {
+    let app = spawn_app_with_captcha(spawn_verify_stub()).await;
+
+    let errors = errors(submit(&app, None).await).await;
+
+    assert_eq!(
+        Some(&Some("Captcha may not be empty.".to_owned())),
+        errors.get("captcha")
+    );
+    assert_eq!(Some(&None), errors.get("name"));
+}
+
+#[actix_rt::test]
+async fn rejected_captcha_returns_a_captcha_error() {
+    let app = spawn_app_with_captcha(spawn_verify_stub()).await;
+
+    let errors = errors(submit(&app, Some("ruh-roh")).await).await;
+
+    assert_eq!(
+        Some(&Some("Captcha verification failed.".to_owned())),
+        errors.get("captcha")
+    );
+}
+
+#[actix_rt::test]
+async fn unreachable_provider_returns_a_captcha_error() {
+    let app = spawn_app_with_captcha("http://127.0.0.1:9/siteverify".to_owned()).await;
+
+    let errors = errors(submit(&app, Some(VALID_RESPONSE)).await).await;
+
+    assert_eq!(
+        Some(&Some("Captcha could not be verified.".to_owned())),
+        errors.get("captcha")
+    );
+}
+
+#[actix_rt::test]
+async fn unresponsive_provider_times_out_with_a_captcha_error() {
+    // Accepts connections without ever answering them.
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("Unable to bind listener.");
+    let port = listener.local_addr().unwrap().port();
+    let app = spawn_app_with_captcha(format!("http://127.0.0.1:{}/siteverify", port)).await;
+
+    let errors = errors(submit(&app, Some(VALID_RESPONSE)).await).await;
+
+    assert_eq!(
+        Some(&Some("Captcha could not be verified.".to_owned())),
+        errors.get("captcha")
+    );
+    drop(listener);
+}
@@ -0,0 +1,162 @@
+mod common;
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use common::spawn_app_with;
+use contact_api::settings::{
+    ChatSettings, ChatWebhookSettings, MatrixSettings, MattermostSettings, NotifierKind,
+    NotifyPolicy, NotifySettings,
+};
+
+#[derive(Debug, Clone)]
+struct Request {
+    method: String,
+    path: String,
+    authorization: Option<String>,
+    json: serde_json::Value,
+}
+
+#[derive(Default)]
+struct Received(Mutex<Vec<Request>>);
+
+async fn receive(
+    request: HttpRequest,
+    json: web::Json<serde_json::Value>,
+    received: web::Data<Received>,
+) -> HttpResponse {
+    received.0.lock().unwrap().push(Request {
+        method: request.method().to_string(),
+        path: request.path().to_owned(),
+        authorization: request
+            .headers()
+            .get("Authorization")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned),
+        json: json.into_inner(),
+    });
+
+    HttpResponse::Ok().json(serde_json::json!({}))
+}
+
+/// A local stand-in for every platform, recording whatever is sent to it.
+fn spawn_stub() -> (String, web::Data<Received>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = web::Data::new(Received::default());
+
+    let data = received.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .default_service(web::to(receive))
+    })
+    .listen(listener)
+    .unwrap()
+    .run();
+    tokio::spawn(server);
+
+    (format!("http://127.0.0.1:{}", port), received)
+}
+
+async fn wait_for(received: &Received, count: usize) -> Vec<Request> {
+    for _ in 0..50 {
+        let requests = received.0.lock().unwrap().clone();
+        if requests.len() >= count {
+            return requests;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("Chat notifications were not received.");
+}
+
+#[actix_rt::test]
+async fn submissions_are_posted_to_every_chat() {
+    let (url, received) = spawn_stub();
+    let webhook = |path: &str| ChatWebhookSettings {
+        webhook_url: format!("{}/{}", url, path),
+        timeout_secs: 5,
+    };
+
+    let app = spawn_app_with(|settings| {
+        settings.chat = Some(ChatSettings {
+            slack: Some(webhook("slack")),
+            discord: Some(webhook("discord")),
+            mattermost: Some(MattermostSettings {
+                webhook_url: format!("{}/mattermost", url),
+                channel: Some("support".to_owned()),
+                timeout_secs: 5,
+            }),
+            matrix: Some(MatrixSettings {
+                homeserver_url: url.clone(),
+                access_token: "scooby-snack".to_owned(),
+                room_id: "!gang:mystery.van".to_owned(),
+                timeout_secs: 5,
+            }),
+        });
+        settings.notify = Some(NotifySettings {
+            policy: NotifyPolicy::All,
+            notifiers: Some(vec![
+                NotifierKind::Slack,
+                NotifierKind::Discord,
+                NotifierKind::Mattermost,
+                NotifierKind::Matrix,
+            ]),
+        });
+    })
+    .await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Daphne"),
+            ("email", "daphne@mystery.van"),
+            ("message", "Jeepers! <Freddie> *vanished*"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let requests = wait_for(&received, 4).await;
+    let request = |path: &str| {
+        requests
+            .iter()
+            .find(|request| request.path.starts_with(path))
+            .unwrap_or_else(|| panic!("Nothing was sent to {}.", path))
+    };
+
+    let slack = &request("/slack").json;
+    assert_eq!(
+        "New contact from Daphne",
+        slack["blocks"][0]["text"]["text"]
+    );
+    assert_eq!(
+        "Jeepers! &lt;Freddie&gt; *vanished*",
+        slack["blocks"][2]["text"]["text"]
+    );
+
+    let discord = &request("/discord").json;
+    assert_eq!(
+        "Jeepers! <Freddie\\> \\*vanished\\*",
+        discord["embeds"][0]["description"]
+    );
+
+    let mattermost = &request("/mattermost").json;
+    assert_eq!("support", mattermost["channel"]);
+    assert_eq!(
+        "daphne@mystery.van",
+        mattermost["attachments"][0]["fields"][0]["value"]
+    );
+
+    let matrix = request("/_matrix/client/v3/rooms/!gang:mystery.van/send/m.room.message/");
+    assert_eq!("PUT", matrix.method);
+    assert_eq!(Some("Bearer scooby-snack"), matrix.authorization.as_deref());
+    assert_eq!("org.matrix.custom.html", matrix.json["format"]);
+    assert!(matrix.json["formatted_body"]
+        .as_str()
+        .unwrap()
+        .contains("Jeepers! &lt;Freddie&gt; *vanished*"));
+}
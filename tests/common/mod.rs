@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 use contact_api::settings::EmailSettings;
 use contact_api::settings::Settings;
 
@@ -6,23 +8,41 @@ pub struct TestApp {
     pub email_settings: EmailSettings,
 }
 
+fn temp_path(name: &str) -> String {
+    let dir = std::env::temp_dir().join(format!("contact-api-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).expect("Unable to create temp dir.");
+    dir.join(name).to_string_lossy().into_owned()
+}
+
 pub async fn spawn_app() -> TestApp {
+    spawn_app_with(|_| {}).await
+}
+
+pub async fn spawn_app_with(configure: impl FnOnce(&mut Settings)) -> TestApp {
     let settings = {
         let mut settings = Settings::new().expect("Unable to read settings.");
         settings.http.port = 0;
         settings.email.from = format!("{}@test.fake", uuid::Uuid::new_v4());
+        settings.email.backup_dir = temp_path("emails");
+        settings.outbox.path = temp_path("outbox.db");
+        if let Some(storage) = settings.storage.as_mut() {
+            storage.url = format!("sqlite://{}?mode=rwc", temp_path("submissions.db"));
+        }
+        configure(&mut settings);
         settings
     };
     let host = settings.http.host.clone();
+    let email_settings = settings.email.clone();
 
-    let app =
-        contact_api::start(settings.http, settings.email.clone()).expect("Unable to start app");
+    let app = contact_api::start(settings)
+        .await
+        .expect("Unable to start app");
     let address = format!("http://{}:{}", host, app.port);
 
     tokio::spawn(app.server);
 
     TestApp {
         address,
-        email_settings: settings.email,
+        email_settings,
     }
 }
@@ -4,7 +4,11 @@ mod common;
 
 use std::collections::HashMap;
 
-use common::spawn_app;
+use contact_api::settings::{
+    LengthSettings, NotifierKind, NotifyPolicy, NotifySettings, ValidationSettings, WebhookSettings,
+};
+
+use common::{spawn_app, spawn_app_with};
 
 struct Form<'f> {
     name: &'f str,
@@ -33,7 +37,7 @@ async fn submit(
     params: &[(&str, &str)],
 ) -> reqwest::Response {
     client
-        .post(&format!("{}/", &addr))
+        .post(format!("{}/", &addr))
         .form(&params)
         .send()
         .await
@@ -57,6 +61,47 @@ struct SearchResponse {
     items: Vec<Item>,
 }
 
+async fn search_mailhog(client: &reqwest::Client, app: &common::TestApp) -> SearchResponse {
+    search_mailhog_from(client, app, &app.email_settings.from).await
+}
+
+async fn query_mailhog_from(
+    client: &reqwest::Client,
+    app: &common::TestApp,
+    from: &str,
+) -> SearchResponse {
+    client
+        .get(format!(
+            "http://{}:{}/api/v2/search",
+            app.email_settings.mailhog_host, app.email_settings.mailhog_port
+        ))
+        .query(&[("kind", "from"), ("query", from)])
+        .send()
+        .await
+        .expect("Unable to reach mail hog")
+        .json::<SearchResponse>()
+        .await
+        .expect("Unable to parse response.")
+}
+
+async fn search_mailhog_from(
+    client: &reqwest::Client,
+    app: &common::TestApp,
+    from: &str,
+) -> SearchResponse {
+    for _ in 0..50 {
+        let search_response = query_mailhog_from(client, app, from).await;
+
+        if search_response.total > 0 {
+            return search_response;
+        }
+
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+
+    panic!("No email was delivered to mail hog.");
+}
+
 #[actix_rt::test]
 async fn posting_contact_with_valid_data_returns_a_202() {
     let app = spawn_app().await;
@@ -73,63 +118,31 @@ async fn posting_contact_with_valid_data_returns_a_202() {
 
     let response = submit(&client, &app.address, &params).await;
 
-    assert_eq!(reqwest::StatusCode::NO_CONTENT, response.status());
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
     assert_eq!(Some(0), response.content_length());
 
-    let mail_response = client
-        .get(&format!(
-            "http://{}:{}/api/v2/search",
-            app.email_settings.mailhog_host, app.email_settings.mailhog_port
-        ))
-        .query(&[("kind", "from"), ("query", &app.email_settings.from)])
-        .send()
-        .await
-        .expect("Unable to reach mail hog");
-
-    assert_eq!(reqwest::StatusCode::OK, mail_response.status());
-
-    let search_response = mail_response
-        .json::<SearchResponse>()
-        .await
-        .expect("Unable to parse response.");
-
-    assert_eq!(1, search_response.total);
+    let search_response = search_mailhog(&client, &app).await;
 
     let content = search_response
         .items
         .into_iter()
-        .nth(0)
+        .next()
         .expect("There should of been one email.")
         .Content;
 
     assert_eq!("Let's solve some mysteries, dude.", &content.Body);
 
-    let from = content
-        .Headers
-        .get("From")
-        .unwrap()
-        .into_iter()
-        .nth(0)
-        .unwrap();
+    let from = content.Headers.get("From").unwrap().first().unwrap();
     assert_eq!(&app.email_settings.from, from);
 
-    let subject = content
-        .Headers
-        .get("Subject")
-        .unwrap()
-        .into_iter()
-        .nth(0)
-        .unwrap();
+    let subject = content.Headers.get("Subject").unwrap().first().unwrap();
     assert_eq!("Shaggy (scooby@mystery.van)", subject);
 
-    let to = content
-        .Headers
-        .get("To")
-        .unwrap()
-        .into_iter()
-        .nth(0)
-        .unwrap();
+    let to = content.Headers.get("To").unwrap().first().unwrap();
     assert_eq!("bob@fake.fake, beth@fake.fake, george@other.fake", to);
+
+    let reply_to = content.Headers.get("Reply-To").unwrap().first().unwrap();
+    assert_eq!("Shaggy <scooby@mystery.van>", reply_to);
 }
 
 #[actix_rt::test]
@@ -216,6 +229,37 @@ async fn missing_email_returns_a_400() {
     assert_eq!(None, errors.message);
 }
 
+#[actix_rt::test]
+async fn email_with_two_at_signs_returns_a_400() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Shaggy",
+        email: "scooby@@mystery.van",
+        message: "Let's solve some mysteries, dude.",
+    };
+
+    let params = construct_params(&form);
+
+    let response = submit(&client, &app.address, &params).await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let errors = response
+        .json::<ErrorBody>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(None, errors.name);
+    assert_eq!(
+        Some(String::from("Email may only contain one @ symbol.")),
+        errors.email
+    );
+    assert_eq!(None, errors.message);
+}
+
 #[actix_rt::test]
 async fn all_whitespace_for_email_returns_a_400() {
     let app = spawn_app().await;
@@ -336,3 +380,701 @@ async fn everything_left_empty_returns_a_400() {
         errors.message
     );
 }
+
+#[actix_rt::test]
+async fn posting_contact_as_json_sends_an_email() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .json(&HashMap::from([
+            ("name", "Velma"),
+            ("email", "velma@mystery.van"),
+            ("message", "Jinkies!"),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let search_response = search_mailhog(&client, &app).await;
+    assert_eq!(1, search_response.total);
+
+    let content = search_response.items.into_iter().next().unwrap().Content;
+    assert_eq!("Jinkies!", &content.Body);
+
+    let subject = content.Headers.get("Subject").unwrap().first().unwrap();
+    assert_eq!("Velma (velma@mystery.van)", subject);
+}
+
+#[actix_rt::test]
+async fn posting_contact_as_multipart_sends_an_email() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let form = reqwest::multipart::Form::new()
+        .text("name", "Fred")
+        .text("email", "fred@mystery.van")
+        .text("message", "Let's split up, gang.");
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .multipart(form)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let search_response = search_mailhog(&client, &app).await;
+    assert_eq!(1, search_response.total);
+
+    let content = search_response.items.into_iter().next().unwrap().Content;
+    assert_eq!("Let's split up, gang.", &content.Body);
+}
+
+#[actix_rt::test]
+async fn json_with_missing_fields_returns_field_errors() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .json(&HashMap::from([("name", "Daphne")]))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let errors = response
+        .json::<ErrorBody>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(None, errors.name);
+    assert_eq!(Some(String::from("Email may not be empty.")), errors.email);
+    assert_eq!(
+        Some(String::from("Message may not be empty.")),
+        errors.message
+    );
+}
+
+#[actix_rt::test]
+async fn malformed_json_returns_a_400() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "application/json")
+        .body("{\"name\": ")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let errors = response
+        .json::<HashMap<String, Option<String>>>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(
+        Some(&Some(String::from("Request body is malformed."))),
+        errors.get("body")
+    );
+    assert_eq!(Some(&None), errors.get("email"));
+}
+
+#[actix_rt::test]
+async fn oversized_bodies_return_a_413() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let message = "Jinkies! ".repeat(2000);
+    let response = submit(
+        &client,
+        &app.address,
+        &[
+            ("name", "Velma"),
+            ("email", "velma@mystery.van"),
+            ("message", &message),
+        ],
+    )
+    .await;
+
+    assert_eq!(reqwest::StatusCode::PAYLOAD_TOO_LARGE, response.status());
+
+    let errors = response
+        .json::<HashMap<String, Option<String>>>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(
+        Some(&Some(String::from("Request body is too large."))),
+        errors.get("body")
+    );
+    assert_eq!(Some(&None), errors.get("email"));
+}
+
+#[actix_rt::test]
+async fn too_many_extra_fields_return_a_400() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let mut body: HashMap<String, String> = (0..100)
+        .map(|index| (format!("clue{}", index), "Jinkies!".to_owned()))
+        .collect();
+    body.insert("name".to_owned(), "Velma".to_owned());
+    body.insert("email".to_owned(), "velma@mystery.van".to_owned());
+    body.insert("message".to_owned(), "My glasses!".to_owned());
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .json(&body)
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+}
+
+#[actix_rt::test]
+async fn overlong_field_names_return_a_400() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let name = "clue".repeat(100);
+    let response = client
+        .post(format!("{}/", &app.address))
+        .json(&HashMap::from([
+            ("name", "Velma"),
+            ("email", "velma@mystery.van"),
+            ("message", "My glasses!"),
+            (name.as_str(), "Jinkies!"),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+}
+
+#[actix_rt::test]
+async fn oversized_extra_fields_return_a_413() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let clue = "Jinkies! ".repeat(2000);
+    let response = client
+        .post(format!("{}/", &app.address))
+        .json(&HashMap::from([
+            ("name", "Velma"),
+            ("email", "velma@mystery.van"),
+            ("message", "My glasses!"),
+            ("clue", &clue),
+        ]))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::PAYLOAD_TOO_LARGE, response.status());
+}
+
+#[actix_rt::test]
+async fn unsupported_content_type_returns_a_415() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .header("Content-Type", "text/plain")
+        .header("Accept-Language", "fr")
+        .body("Zoinks!")
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(
+        reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        response.status()
+    );
+    assert_eq!("fr", response.headers().get("Content-Language").unwrap());
+
+    let errors = response
+        .json::<HashMap<String, Option<String>>>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(
+        Some(&Some(String::from(
+            "Le corps de la requête doit être au format json, urlencoded ou multipart form data."
+        ))),
+        errors.get("body")
+    );
+}
+
+#[actix_rt::test]
+async fn smtp_outage_still_accepts_contact() {
+    let app = spawn_app_with(|settings| settings.email.smtp_port = 1).await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Scrappy",
+        email: "scrappy@mystery.van",
+        message: "Puppy power!",
+    };
+
+    let params = construct_params(&form);
+
+    let response = submit(&client, &app.address, &params).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert_eq!(
+        1,
+        std::fs::read_dir(&app.email_settings.backup_dir)
+            .expect("Unable to read backup dir.")
+            .count()
+    );
+}
+
+#[actix_rt::test]
+async fn configured_templates_are_sent_as_multipart_alternative() {
+    let dir = std::env::temp_dir().join(format!("templates-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("subject.txt"), "New message from {{ name }}").unwrap();
+    std::fs::write(dir.join("body.txt"), "{{ name }} wrote: {{ message }}").unwrap();
+    std::fs::write(dir.join("body.html"), "<p>{{ message }}</p>").unwrap();
+
+    let app = spawn_app_with(|settings| {
+        let path = |name: &str| Some(dir.join(name).to_string_lossy().into_owned());
+        settings.email.subject_template = path("subject.txt");
+        settings.email.text_template = path("body.txt");
+        settings.email.html_template = path("body.html");
+    })
+    .await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Daphne",
+        email: "daphne@mystery.van",
+        message: "Jeepers <3",
+    };
+
+    let params = construct_params(&form);
+
+    let response = submit(&client, &app.address, &params).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let search_response = search_mailhog(&client, &app).await;
+    let content = search_response.items.into_iter().next().unwrap().Content;
+
+    let subject = content.Headers.get("Subject").unwrap().first().unwrap();
+    assert_eq!("New message from Daphne", subject);
+
+    let content_type = content
+        .Headers
+        .get("Content-Type")
+        .unwrap()
+        .first()
+        .unwrap();
+    assert!(content_type.starts_with("multipart/alternative"));
+
+    assert!(content.Body.contains("Daphne wrote: Jeepers <3"));
+    assert!(content.Body.contains("<p>Jeepers &lt;3</p>"));
+}
+
+#[actix_rt::test]
+async fn submitter_can_be_sent_as_from_with_a_sender_header() {
+    let app = spawn_app_with(|settings| settings.email.from_submitter = true).await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Fred",
+        email: "fred@mystery.van",
+        message: "Let's split up, gang.",
+    };
+
+    let params = construct_params(&form);
+
+    let response = submit(&client, &app.address, &params).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let content = search_mailhog(&client, &app)
+        .await
+        .items
+        .into_iter()
+        .next()
+        .unwrap()
+        .Content;
+
+    let from = content.Headers.get("From").unwrap().first().unwrap();
+    assert_eq!("Fred <fred@mystery.van>", from);
+
+    let sender = content.Headers.get("Sender").unwrap().first().unwrap();
+    assert_eq!(&app.email_settings.from, sender);
+}
+
+#[actix_rt::test]
+async fn acknowledgement_is_sent_to_the_submitter() {
+    let acknowledgement_from = format!("{}@test.fake", uuid::Uuid::new_v4());
+
+    let app = spawn_app_with(|settings| {
+        let acknowledgement = settings.email.acknowledgement.as_mut().unwrap();
+        acknowledgement.enabled = true;
+        acknowledgement.from = acknowledgement_from.clone();
+    })
+    .await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Velma",
+        email: "velma@mystery.van",
+        message: "My glasses!",
+    };
+
+    let params = construct_params(&form);
+
+    let response = submit(&client, &app.address, &params).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let search_response = search_mailhog_from(&client, &app, &acknowledgement_from).await;
+    assert_eq!(1, search_response.total);
+
+    let content = search_response.items.into_iter().next().unwrap().Content;
+
+    let to = content.Headers.get("To").unwrap().first().unwrap();
+    assert_eq!("Velma <velma@mystery.van>", to);
+
+    let subject = content.Headers.get("Subject").unwrap().first().unwrap();
+    assert_eq!("We received your message", subject);
+}
+
+#[actix_rt::test]
+async fn spam_is_not_acknowledged() {
+    let acknowledgement_from = format!("{}@test.fake", uuid::Uuid::new_v4());
+
+    let app = spawn_app_with(|settings| {
+        let acknowledgement = settings.email.acknowledgement.as_mut().unwrap();
+        acknowledgement.enabled = true;
+        acknowledgement.from = acknowledgement_from.clone();
+    })
+    .await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Velma",
+        email: "velma@mystery.van",
+        message: "Casino bonus, act now!",
+    };
+
+    let response = submit(&client, &app.address, &construct_params(&form)).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    search_mailhog(&client, &app).await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(
+        0,
+        query_mailhog_from(&client, &app, &acknowledgement_from)
+            .await
+            .total
+    );
+}
+
+#[actix_rt::test]
+async fn undelivered_submissions_are_not_acknowledged() {
+    let acknowledgement_from = format!("{}@test.fake", uuid::Uuid::new_v4());
+
+    let app = spawn_app_with(|settings| {
+        let acknowledgement = settings.email.acknowledgement.as_mut().unwrap();
+        acknowledgement.enabled = true;
+        acknowledgement.from = acknowledgement_from.clone();
+        settings.webhooks = vec![WebhookSettings {
+            url: "http://127.0.0.1:1/hook".to_owned(),
+            secret: "scooby-snack".to_owned(),
+            timeout_secs: 1,
+            max_attempts: 1,
+            retry_delay_secs: 0,
+            template: None,
+        }];
+        settings.notify = Some(NotifySettings {
+            policy: NotifyPolicy::BestEffort,
+            notifiers: Some(vec![NotifierKind::Webhooks]),
+        });
+    })
+    .await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Velma",
+        email: "velma@mystery.van",
+        message: "My glasses!",
+    };
+
+    let response = submit(&client, &app.address, &construct_params(&form)).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
+    assert_eq!(
+        0,
+        query_mailhog_from(&client, &app, &acknowledgement_from)
+            .await
+            .total
+    );
+}
+
+#[actix_rt::test]
+async fn form_submissions_go_to_the_form_recipients() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/forms/support", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("order_number", "1969"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let content = search_mailhog(&client, &app)
+        .await
+        .items
+        .into_iter()
+        .next()
+        .expect("There should of been one email.")
+        .Content;
+
+    let to = content.Headers.get("To").unwrap().first().unwrap();
+    assert_eq!("help@fake.fake", to);
+}
+
+#[actix_rt::test]
+async fn form_submissions_missing_required_fields_return_a_400() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/forms/support", &app.address))
+        .form(&[
+            ("name", ""),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("order_number", " "),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let body = response
+        .json::<HashMap<String, Option<String>>>()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        Some(&Some("This field is required.".to_owned())),
+        body.get("order_number")
+    );
+    assert_eq!(
+        Some(&Some("Name may not be empty.".to_owned())),
+        body.get("name")
+    );
+}
+
+#[actix_rt::test]
+async fn form_submissions_can_redirect_on_success() {
+    let app = spawn_app_with(|settings| {
+        let form = settings.forms.get_mut("support").unwrap();
+        form.redirect_to = Some("https://mystery.van/thanks".to_owned());
+    })
+    .await;
+
+    let response = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .post(format!("{}/forms/support", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("order_number", "1969"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::SEE_OTHER, response.status());
+    assert_eq!(
+        "https://mystery.van/thanks",
+        response.headers().get("Location").unwrap()
+    );
+}
+
+#[actix_rt::test]
+async fn unknown_forms_return_a_404() {
+    let app = spawn_app().await;
+
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/forms/haunted", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::NOT_FOUND, response.status());
+}
+
+#[actix_rt::test]
+async fn configured_length_limits_are_reported_with_the_limit() {
+    let app = spawn_app_with(|settings| {
+        settings.validation = Some(ValidationSettings {
+            email: None,
+            name: Some(LengthSettings { min: 3, max: 200 }),
+            message: Some(LengthSettings { min: 1, max: 10 }),
+        });
+    })
+    .await;
+
+    let client = reqwest::Client::new();
+
+    let form = Form {
+        name: "Al",
+        email: "scooby@mystery.van",
+        message: "Zoinks, where are you Scooby?",
+    };
+
+    let response = submit(&client, &app.address, &construct_params(&form)).await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let errors = response
+        .json::<ErrorBody>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(
+        Some(String::from("Name must be at least 3 characters long.")),
+        errors.name
+    );
+    assert_eq!(None, errors.email);
+    assert_eq!(
+        Some(String::from(
+            "Message may not be longer than 10 characters long."
+        )),
+        errors.message
+    );
+}
+
+#[actix_rt::test]
+async fn form_fields_are_validated_against_the_schema() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/forms/quote", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("phone", "call me maybe"),
+            ("budget", "all of it"),
+            ("seats", "5000"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let errors = response
+        .json::<HashMap<String, Option<String>>>()
+        .await
+        .expect("Unable to read json body.");
+
+    assert_eq!(
+        Some(&Some("This field must be a phone number.".to_owned())),
+        errors.get("phone")
+    );
+    assert_eq!(
+        Some(&Some(
+            "This field must be one of the listed options.".to_owned()
+        )),
+        errors.get("budget")
+    );
+    assert_eq!(
+        Some(&Some("This field may not be more than 1000.".to_owned())),
+        errors.get("seats")
+    );
+    assert_eq!(Some(&None), errors.get("name"));
+}
+
+#[actix_rt::test]
+async fn form_fields_are_rendered_into_the_notification_and_undeclared_ones_dropped() {
+    let app = spawn_app().await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/forms/quote", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+            ("company", "Mystery Inc"),
+            ("budget", "5k-20k"),
+            ("seats", "4"),
+            ("newsletter", "on"),
+            ("referral", "Scrappy"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let backup = std::fs::read_dir(&app.email_settings.backup_dir)
+        .expect("Unable to read backup dir.")
+        .next()
+        .expect("There should of been one backup.")
+        .unwrap();
+    let email = std::fs::read_to_string(backup.path()).unwrap();
+
+    assert!(email.contains(
+        "Zoinks!\r\n\r\ncompany: Mystery Inc\r\nbudget: 5k-20k\r\nseats: 4\r\nSubscribe to newsletter: true"
+    ));
+    assert!(!email.contains("Scrappy"));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use contact_api::settings::EmailDomainSettings;
+
+use common::{spawn_app, spawn_app_with, TestApp};
+
+async fn submit(app: &TestApp, email: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[("name", "Shaggy"), ("email", email), ("message", "Zoinks!")])
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[actix_rt::test]
+async fn disposable_domains_return_a_400() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, "bot@mailinator.com").await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    let json = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!("Email domain is not allowed.", json["email"]);
+}
+
+#[actix_rt::test]
+async fn denied_domains_return_a_400_unless_allowed() {
+    let app = spawn_app_with(|settings| {
+        settings.email_domains = Some(EmailDomainSettings {
+            disposable_list_path: None,
+            deny: vec!["spam.fake".to_owned()],
+            allow: vec!["good.spam.fake".to_owned()],
+        });
+    })
+    .await;
+
+    let response = submit(&app, "bot@mail.spam.fake").await;
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let response = submit(&app, "shaggy@good.spam.fake").await;
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+}
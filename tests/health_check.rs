@@ -9,7 +9,7 @@ async fn health_check_works() {
     let client = reqwest::Client::new();
 
     let response = client
-        .get(&format!("{}/health-check", &app.address))
+        .get(format!("{}/health-check", &app.address))
         .send()
         .await
         .expect("Failed to execute request");
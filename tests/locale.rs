@@ -0,0 +1,83 @@
+mod common;
+
+use common::{spawn_app, TestApp};
+
+async fn submit(
+    app: &TestApp,
+    accept_language: &str,
+    params: &[(&str, &str)],
+) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .header("Accept-Language", accept_language)
+        .form(params)
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+#[actix_rt::test]
+async fn errors_are_in_the_accept_language() {
+    let app = spawn_app().await;
+
+    let response = submit(
+        &app,
+        "fr-CA,fr;q=0.9,en;q=0.8",
+        &[
+            ("name", ""),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+        ],
+    )
+    .await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    assert_eq!("fr", response.headers()["Content-Language"]);
+    let errors = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!("Le nom ne peut pas être vide.", errors["name"]);
+}
+
+#[actix_rt::test]
+async fn lang_field_overrides_the_accept_language() {
+    let app = spawn_app().await;
+
+    let response = submit(
+        &app,
+        "fr",
+        &[
+            ("name", "Shaggy"),
+            ("email", ""),
+            ("message", "Zoinks!"),
+            ("lang", "es"),
+        ],
+    )
+    .await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    let errors = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!(
+        "El correo electrónico no puede estar vacío.",
+        errors["email"]
+    );
+    assert!(errors.get("lang").is_none());
+}
+
+#[actix_rt::test]
+async fn unsupported_languages_fall_back_to_english() {
+    let app = spawn_app().await;
+
+    let response = submit(
+        &app,
+        "de-DE,ja;q=0.5",
+        &[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", ""),
+        ],
+    )
+    .await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    let errors = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!("Message may not be empty.", errors["message"]);
+}
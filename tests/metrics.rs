@@ -0,0 +1,85 @@
+mod common;
+
+use common::spawn_app;
+
+#[actix_rt::test]
+async fn metrics_are_exposed_in_the_prometheus_text_format() {
+    let app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Fred"),
+            ("email", "fred.mystery.van"),
+            ("message", "Let's split up, gang."),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+
+    let response = client
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Fred"),
+            ("email", "fred@mystery.van"),
+            ("message", "Let's split up, gang."),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let response = client
+        .get(format!("{}/metrics", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::OK, response.status());
+    assert_eq!(
+        "text/plain; version=0.0.4",
+        response.headers()[reqwest::header::CONTENT_TYPE]
+    );
+
+    let metrics = response.text().await.unwrap();
+    for expected in [
+        r#"contact_api_http_requests_total{method="POST",route="/",status="400"} 1"#,
+        r#"contact_api_http_requests_total{method="POST",route="/",status="202"} 1"#,
+        r#"contact_api_http_request_duration_seconds_count{method="POST",route="/",status="202"} 1"#,
+        r#"contact_api_validation_failures_total{error="email-missing-at-sign",field="email"} 1"#,
+        r#"contact_api_email_send_duration_seconds_count{transport="file"} 1"#,
+        r#"contact_api_spam_verdicts_total{verdict="deliver"} 1"#,
+        "contact_api_outbox_pending ",
+    ]
+    .iter()
+    {
+        assert!(metrics.contains(expected), "{} missing from {}", expected, metrics);
+    }
+}
+
+#[actix_rt::test]
+async fn extension_methods_share_one_label() {
+    let app = spawn_app().await;
+    let client = reqwest::Client::new();
+
+    let method = reqwest::Method::from_bytes(b"JINKIES").unwrap();
+    client
+        .request(method, format!("{}/", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    let metrics = client
+        .get(format!("{}/metrics", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request")
+        .text()
+        .await
+        .unwrap();
+
+    assert!(metrics.contains(r#"method="other""#), "{}", metrics);
+    assert!(!metrics.contains("JINKIES"), "{}", metrics);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use std::time::Duration;
+
+use common::spawn_app_with;
+use contact_api::settings::{NotifierKind, NotifyPolicy, NotifySettings, WebhookSettings};
+use sqlx::sqlite::SqlitePool;
+
+/// Submits with email and an unreachable webhook as notifiers, returning the stored status.
+async fn status_with(policy: NotifyPolicy, expected: &str) -> String {
+    let path = std::env::temp_dir().join(format!("submissions-{}.db", uuid::Uuid::new_v4()));
+    let url = format!("sqlite://{}?mode=rwc", path.to_string_lossy());
+
+    let app = spawn_app_with(|settings| {
+        if let Some(storage) = settings.storage.as_mut() {
+            storage.url = url.clone();
+        }
+        settings.outbox.max_attempts = 1;
+        settings.webhooks = vec![WebhookSettings {
+            url: "http://127.0.0.1:1/hook".to_owned(),
+            secret: "scooby-snack".to_owned(),
+            timeout_secs: 1,
+            max_attempts: 1,
+            retry_delay_secs: 0,
+            template: None,
+        }];
+        settings.notify = Some(NotifySettings {
+            policy,
+            notifiers: Some(vec![NotifierKind::Email, NotifierKind::Webhooks]),
+        });
+    })
+    .await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Fred"),
+            ("email", "fred@mystery.van"),
+            ("message", "Let's split up, gang."),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+
+    let pool = SqlitePool::connect(&url).await.unwrap();
+    let mut status = String::new();
+    for _ in 0..50 {
+        status = sqlx::query_scalar("SELECT status FROM submissions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        if status == expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    status
+}
+
+#[actix_rt::test]
+async fn all_policy_fails_delivery_when_any_notifier_fails() {
+    assert_eq!("dead", status_with(NotifyPolicy::All, "dead").await);
+}
+
+#[actix_rt::test]
+async fn any_policy_delivers_when_one_notifier_succeeds() {
+    assert_eq!("sent", status_with(NotifyPolicy::Any, "sent").await);
+}
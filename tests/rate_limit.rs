@@ -0,0 +1,103 @@
+mod common;
+
+use contact_api::settings::{BucketSettings, RateLimitSettings};
+
+use common::{spawn_app_with, TestApp};
+
+fn bucket(capacity: u32) -> Option<BucketSettings> {
+    Some(BucketSettings {
+        capacity,
+        refill_secs: 60,
+    })
+}
+
+async fn submit(app: &TestApp, email: &str, forwarded_for: Option<&str>) -> reqwest::Response {
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[("name", "Shaggy"), ("email", email), ("message", "Zoinks!")]);
+
+    if let Some(forwarded_for) = forwarded_for {
+        request = request.header("X-Forwarded-For", forwarded_for);
+    }
+
+    request.send().await.expect("Failed to execute request")
+}
+
+#[actix_rt::test]
+async fn clients_over_the_ip_limit_get_a_429_with_retry_after() {
+    let app = spawn_app_with(|settings| {
+        settings.http.rate_limit = Some(RateLimitSettings {
+            per_ip: bucket(2),
+            per_email: None,
+        });
+    })
+    .await;
+
+    for _ in 0..2 {
+        let response = submit(&app, "scooby@mystery.van", None).await;
+        assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    }
+
+    let response = submit(&app, "scooby@mystery.van", None).await;
+
+    assert_eq!(reqwest::StatusCode::TOO_MANY_REQUESTS, response.status());
+    assert_eq!("60", response.headers().get("Retry-After").unwrap());
+}
+
+#[actix_rt::test]
+async fn email_addresses_over_the_limit_get_a_429() {
+    let app = spawn_app_with(|settings| {
+        settings.http.rate_limit = Some(RateLimitSettings {
+            per_ip: None,
+            per_email: bucket(1),
+        });
+    })
+    .await;
+
+    let first = submit(&app, "scooby@mystery.van", None).await;
+    let again = submit(&app, "Scooby@Mystery.Van", None).await;
+    let other = submit(&app, "velma@mystery.van", None).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, first.status());
+    assert_eq!(reqwest::StatusCode::TOO_MANY_REQUESTS, again.status());
+    assert!(again.headers().contains_key("Retry-After"));
+    assert_eq!(reqwest::StatusCode::ACCEPTED, other.status());
+}
+
+#[actix_rt::test]
+async fn forwarded_for_is_ignored_from_untrusted_peers() {
+    let app = spawn_app_with(|settings| {
+        settings.http.trusted_proxies = Vec::new();
+        settings.http.rate_limit = Some(RateLimitSettings {
+            per_ip: bucket(1),
+            per_email: None,
+        });
+    })
+    .await;
+
+    let first = submit(&app, "scooby@mystery.van", Some("203.0.113.1")).await;
+    let second = submit(&app, "scooby@mystery.van", Some("203.0.113.2")).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, first.status());
+    assert_eq!(reqwest::StatusCode::TOO_MANY_REQUESTS, second.status());
+}
+
+#[actix_rt::test]
+async fn forwarded_for_identifies_clients_behind_trusted_proxies() {
+    let app = spawn_app_with(|settings| {
+        settings.http.trusted_proxies = vec!["127.0.0.1".parse().unwrap()];
+        settings.http.rate_limit = Some(RateLimitSettings {
+            per_ip: bucket(1),
+            per_email: None,
+        });
+    })
+    .await;
+
+    let first = submit(&app, "scooby@mystery.van", Some("203.0.113.1")).await;
+    let second = submit(&app, "scooby@mystery.van", Some("203.0.113.2")).await;
+    let again = submit(&app, "scooby@mystery.van", Some("203.0.113.1")).await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, first.status());
+    assert_eq!(reqwest::StatusCode::ACCEPTED, second.status());
+    assert_eq!(reqwest::StatusCode::TOO_MANY_REQUESTS, again.status());
+}
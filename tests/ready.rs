@@ -0,0 +1,93 @@
+mod common;
+
+use common::{spawn_app_with, TestApp};
+use contact_api::settings::{ReadinessCheck, ReadinessSettings};
+
+fn readiness(cache_ttl_secs: u64, optional: Vec<ReadinessCheck>) -> Option<ReadinessSettings> {
+    Some(ReadinessSettings {
+        cache_ttl_secs,
+        timeout_secs: 5,
+        optional,
+    })
+}
+
+async fn ready(app: &TestApp) -> (reqwest::StatusCode, serde_json::Value) {
+    let response = reqwest::Client::new()
+        .get(format!("{}/ready", &app.address))
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    (response.status(), response.json().await.unwrap())
+}
+
+#[actix_rt::test]
+async fn ready_when_every_check_passes() {
+    let app = spawn_app_with(|settings| settings.readiness = readiness(0, vec![])).await;
+
+    let (status, body) = ready(&app).await;
+
+    assert_eq!(reqwest::StatusCode::OK, status);
+    assert_eq!(true, body["ready"]);
+    for check in &["smtp", "backup_dir", "outbox", "storage"] {
+        assert_eq!("up", body["checks"][check]["status"], "{} is down", check);
+        assert!(body["checks"][check]["latency_ms"].is_u64());
+    }
+}
+
+#[actix_rt::test]
+async fn unavailable_when_smtp_is_down() {
+    let app = spawn_app_with(|settings| {
+        settings.email.smtp_port = 1;
+        settings.readiness = readiness(0, vec![]);
+    })
+    .await;
+
+    let (status, body) = ready(&app).await;
+
+    assert_eq!(reqwest::StatusCode::SERVICE_UNAVAILABLE, status);
+    assert_eq!(false, body["ready"]);
+    assert_eq!("down", body["checks"]["smtp"]["status"]);
+    assert!(body["checks"]["smtp"]["error"].is_string());
+    assert_eq!("up", body["checks"]["backup_dir"]["status"]);
+}
+
+#[actix_rt::test]
+async fn optional_checks_do_not_fail_readiness() {
+    let app = spawn_app_with(|settings| {
+        settings.email.smtp_port = 1;
+        settings.readiness = readiness(0, vec![ReadinessCheck::Smtp]);
+    })
+    .await;
+
+    let (status, body) = ready(&app).await;
+
+    assert_eq!(reqwest::StatusCode::OK, status);
+    assert_eq!("down", body["checks"]["smtp"]["status"]);
+    assert_eq!(false, body["checks"]["smtp"]["required"]);
+}
+
+#[actix_rt::test]
+async fn unavailable_when_backups_cannot_be_written() {
+    let app = spawn_app_with(|settings| settings.readiness = readiness(0, vec![])).await;
+    std::fs::remove_dir_all(&app.email_settings.backup_dir).unwrap();
+
+    let (status, body) = ready(&app).await;
+
+    assert_eq!(reqwest::StatusCode::SERVICE_UNAVAILABLE, status);
+    assert_eq!("down", body["checks"]["backup_dir"]["status"]);
+}
+
+#[actix_rt::test]
+async fn results_are_cached_for_the_ttl() {
+    let app = spawn_app_with(|settings| settings.readiness = readiness(60, vec![])).await;
+
+    let (status, first) = ready(&app).await;
+    assert_eq!(reqwest::StatusCode::OK, status);
+
+    std::fs::remove_dir_all(&app.email_settings.backup_dir).unwrap();
+
+    let (status, second) = ready(&app).await;
+    assert_eq!(reqwest::StatusCode::OK, status);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,271 @@
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use contact_api::settings::SmtpTls;
+use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::mpsc,
+};
+use tokio_rustls::{
+    rustls::{
+        self,
+        pki_types::{pem::PemObject, CertificateDer, PrivateKeyDer},
+    },
+    TlsAcceptor,
+};
+
+use common::spawn_app_with;
+
+#[derive(Debug, Default)]
+struct Transcript {
+    encrypted: bool,
+    hello: String,
+    auth: Option<String>,
+    data: String,
+}
+
+enum Dialog {
+    StartTls(Transcript),
+    Done(Transcript),
+}
+
+struct SmtpStub {
+    port: u16,
+    ca_path: String,
+    transcripts: mpsc::UnboundedReceiver<Transcript>,
+}
+
+async fn dialog<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut BufReader<S>,
+    mut transcript: Transcript,
+    greet: bool,
+    offer_starttls: bool,
+) -> Dialog {
+    if greet {
+        stream.write_all(b"220 stub ESMTP\r\n").await.unwrap();
+    }
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if stream.read_line(&mut line).await.unwrap() == 0 {
+            return Dialog::Done(transcript);
+        }
+
+        let command = line.trim_end().to_owned();
+        let verb = command.split(' ').next().unwrap_or("").to_uppercase();
+
+        match verb.as_str() {
+            "EHLO" => {
+                transcript.hello = command[5..].to_owned();
+                if offer_starttls && !transcript.encrypted {
+                    stream
+                        .write_all(b"250-stub\r\n250 STARTTLS\r\n")
+                        .await
+                        .unwrap();
+                } else {
+                    stream
+                        .write_all(b"250-stub\r\n250 AUTH PLAIN LOGIN XOAUTH2\r\n")
+                        .await
+                        .unwrap();
+                }
+            }
+            "STARTTLS" => {
+                stream.write_all(b"220 go ahead\r\n").await.unwrap();
+                return Dialog::StartTls(transcript);
+            }
+            "AUTH" => {
+                let mut parts = command.splitn(3, ' ').skip(1);
+                let mechanism = parts.next().unwrap_or("");
+                let response = base64::decode(parts.next().unwrap_or("")).unwrap();
+                transcript.auth = Some(format!(
+                    "{} {}",
+                    mechanism,
+                    String::from_utf8(response).unwrap()
+                ));
+                stream.write_all(b"235 ok\r\n").await.unwrap();
+            }
+            "DATA" => {
+                stream.write_all(b"354 go ahead\r\n").await.unwrap();
+                loop {
+                    line.clear();
+                    stream.read_line(&mut line).await.unwrap();
+                    if line == ".\r\n" {
+                        break;
+                    }
+                    transcript.data.push_str(&line);
+                }
+                stream.write_all(b"250 queued\r\n").await.unwrap();
+            }
+            "QUIT" => {
+                stream.write_all(b"221 bye\r\n").await.unwrap();
+                return Dialog::Done(transcript);
+            }
+            _ => stream.write_all(b"250 ok\r\n").await.unwrap(),
+        }
+    }
+}
+
+async fn spawn_smtp_stub(mode: SmtpTls) -> SmtpStub {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca_params = CertificateParams::new(Vec::new()).unwrap();
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca = ca_params.self_signed(&ca_key).unwrap();
+
+    let key = KeyPair::generate().unwrap();
+    let cert = CertificateParams::new(vec!["localhost".to_owned()])
+        .unwrap()
+        .signed_by(&key, &ca, &ca_key)
+        .unwrap();
+
+    let ca_path = std::env::temp_dir().join(format!("ca-{}.pem", uuid::Uuid::new_v4()));
+    std::fs::write(&ca_path, ca.pem()).unwrap();
+
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .unwrap()
+    .with_no_client_auth()
+    .with_single_cert(
+        vec![CertificateDer::from(cert.der().to_vec())],
+        PrivateKeyDer::from_pem_slice(key.serialize_pem().as_bytes()).unwrap(),
+    )
+    .unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let (sender, transcripts) = mpsc::unbounded_channel();
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+            let acceptor = acceptor.clone();
+            let sender = sender.clone();
+
+            tokio::spawn(async move {
+                let transcript = match mode {
+                    SmtpTls::Implicit => {
+                        let tls = acceptor.accept(socket).await.unwrap();
+                        let transcript = Transcript {
+                            encrypted: true,
+                            ..Transcript::default()
+                        };
+                        match dialog(&mut BufReader::new(tls), transcript, true, false).await {
+                            Dialog::Done(transcript) | Dialog::StartTls(transcript) => transcript,
+                        }
+                    }
+                    _ => {
+                        let mut plain = BufReader::new(socket);
+                        match dialog(&mut plain, Transcript::default(), true, true).await {
+                            Dialog::Done(transcript) => transcript,
+                            Dialog::StartTls(mut transcript) => {
+                                transcript.encrypted = true;
+                                let tls = acceptor.accept(plain.into_inner()).await.unwrap();
+                                match dialog(&mut BufReader::new(tls), transcript, false, true)
+                                    .await
+                                {
+                                    Dialog::Done(transcript) | Dialog::StartTls(transcript) => {
+                                        transcript
+                                    }
+                                }
+                            }
+                        }
+                    }
+                };
+
+                let _ = sender.send(transcript);
+            });
+        }
+    });
+
+    SmtpStub {
+        port,
+        ca_path: ca_path.to_string_lossy().into_owned(),
+        transcripts,
+    }
+}
+
+async fn next_delivery(stub: &mut SmtpStub) -> Transcript {
+    tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            let transcript = stub.transcripts.recv().await.unwrap();
+            if !transcript.data.is_empty() {
+                return transcript;
+            }
+        }
+    })
+    .await
+    .expect("No email was delivered to the smtp stub.")
+}
+
+async fn submit(address: &str) {
+    let response = reqwest::Client::new()
+        .post(format!("{}/", address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", "Zoinks!"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+}
+
+#[actix_rt::test]
+async fn implicit_tls_with_password_delivers_email() {
+    let mut stub = spawn_smtp_stub(SmtpTls::Implicit).await;
+
+    let app = spawn_app_with(|settings| {
+        settings.email.smtp_port = stub.port;
+        settings.email.smtp_tls = SmtpTls::Implicit;
+        settings.email.smtp_ca_cert_path = Some(stub.ca_path.clone());
+        settings.email.smtp_username = Some("shaggy".to_owned());
+        settings.email.smtp_password = Some("scooby snacks".to_owned());
+        settings.email.smtp_hello_name = Some("contact-api.fake".to_owned());
+    })
+    .await;
+
+    submit(&app.address).await;
+
+    let transcript = next_delivery(&mut stub).await;
+
+    assert!(transcript.encrypted);
+    assert_eq!("contact-api.fake", transcript.hello);
+    assert_eq!(
+        Some("PLAIN \0shaggy\0scooby snacks".to_owned()),
+        transcript.auth
+    );
+    assert!(transcript.data.contains("Zoinks!"));
+}
+
+#[actix_rt::test]
+async fn required_starttls_with_xoauth2_delivers_email() {
+    let mut stub = spawn_smtp_stub(SmtpTls::Required).await;
+
+    let app = spawn_app_with(|settings| {
+        settings.email.smtp_port = stub.port;
+        settings.email.smtp_tls = SmtpTls::Required;
+        settings.email.smtp_ca_cert_path = Some(stub.ca_path.clone());
+        settings.email.smtp_username = Some("shaggy".to_owned());
+        settings.email.smtp_xoauth2_token = Some("scooby-token".to_owned());
+    })
+    .await;
+
+    submit(&app.address).await;
+
+    let transcript = next_delivery(&mut stub).await;
+
+    assert!(transcript.encrypted);
+    assert_eq!(
+        Some("XOAUTH2 user=shaggy\x01auth=Bearer scooby-token\x01\x01".to_owned()),
+        transcript.auth
+    );
+    assert!(transcript.data.contains("Zoinks!"));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use contact_api::settings::SpamRule;
+
+use common::{spawn_app, spawn_app_with, TestApp};
+
+async fn submit(app: &TestApp, email: &str, message: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[("name", "Shaggy"), ("email", email), ("message", message)])
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+fn backup(app: &TestApp) -> String {
+    let entry = std::fs::read_dir(&app.email_settings.backup_dir)
+        .expect("Unable to read backup dir.")
+        .next()
+        .expect("There should of been one backup.")
+        .unwrap();
+
+    std::fs::read_to_string(entry.path()).unwrap()
+}
+
+#[actix_rt::test]
+async fn clean_contacts_are_not_tagged() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, "scooby@mystery.van", "Zoinks!").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    let email = backup(&app);
+    assert!(email.contains("Subject: Shaggy (scooby@mystery.van)\r\n"));
+    assert!(!email.contains("X-Spam-Score"));
+}
+
+#[actix_rt::test]
+async fn suspicious_contacts_are_tagged() {
+    let app = spawn_app().await;
+
+    let response = submit(&app, "scooby@mystery.van", "Casino bonus, act now!").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    let email = backup(&app);
+    assert!(email.contains("Subject: [SPAM] Shaggy (scooby@mystery.van)\r\n"));
+    assert!(email.contains("X-Spam-Score: 6\r\n"));
+}
+
+#[actix_rt::test]
+async fn spam_is_quarantined_but_still_accepted() {
+    let app = spawn_app_with(|settings| {
+        let spam = settings.spam.as_mut().unwrap();
+        spam.rules.push(SpamRule::EmailDomains {
+            domains: vec!["spam.fake".to_owned()],
+            points: 10,
+        });
+    })
+    .await;
+
+    let response = submit(&app, "bot@spam.fake", "Zoinks!").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert!(backup(&app).contains("X-Spam-Score: 10\r\n"));
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use actix_web::{web, App, HttpResponse, HttpServer};
+use contact_api::settings::{SpamDaemonKind, SpamDaemonSettings};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+use common::{spawn_app_with, TestApp};
+
+fn score(message: &str) -> f64 {
+    if message.contains("REJECT-ME") {
+        20.5
+    } else if message.contains("QUARANTINE-ME") {
+        12.0
+    } else if message.contains("TAG-ME") {
+        6.3
+    } else {
+        0.0
+    }
+}
+
+async fn checkv2(body: String) -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({
+        "score": score(&body),
+        "required_score": 15.0,
+        "action": "no action",
+    }))
+}
+
+fn spawn_rspamd_stub() -> u16 {
+    let server = HttpServer::new(|| App::new().route("/checkv2", web::post().to(checkv2)))
+        .bind("127.0.0.1:0")
+        .expect("Unable to bind rspamd stub.");
+    let port = server.addrs()[0].port();
+
+    tokio::spawn(server.run());
+
+    port
+}
+
+async fn spawn_spamd_stub() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    tokio::spawn(async move {
+        loop {
+            let (socket, _) = listener.accept().await.unwrap();
+
+            tokio::spawn(async move {
+                let mut reader = BufReader::new(socket);
+                let mut length = 0;
+                let mut line = String::new();
+
+                loop {
+                    line.clear();
+                    reader.read_line(&mut line).await.unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(value) = line.strip_prefix("Content-length:") {
+                        length = value.trim().parse().unwrap();
+                    }
+                }
+
+                let mut message = vec![0; length];
+                reader.read_exact(&mut message).await.unwrap();
+                let score = score(&String::from_utf8_lossy(&message));
+
+                let response = format!(
+                    "SPAMD/1.5 0 EX_OK\r\nSpam: {} ; {} / 5.0\r\n\r\n",
+                    if score >= 5.0 { "True" } else { "False" },
+                    score
+                );
+                reader
+                    .get_mut()
+                    .write_all(response.as_bytes())
+                    .await
+                    .unwrap();
+            });
+        }
+    });
+
+    port
+}
+
+async fn spawn_app_with_daemon(kind: SpamDaemonKind, port: u16) -> TestApp {
+    spawn_app_with(|settings| {
+        settings.email.spam_daemon = Some(SpamDaemonSettings {
+            kind,
+            host: "127.0.0.1".to_owned(),
+            port,
+            timeout_secs: 5,
+            tag_threshold: 5,
+            quarantine_threshold: 10,
+            reject_threshold: 15,
+        });
+    })
+    .await
+}
+
+async fn submit(app: &TestApp, message: &str) -> reqwest::Response {
+    reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Shaggy"),
+            ("email", "scooby@mystery.van"),
+            ("message", message),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request")
+}
+
+fn backups(app: &TestApp) -> Vec<String> {
+    std::fs::read_dir(&app.email_settings.backup_dir)
+        .expect("Unable to read backup dir.")
+        .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect()
+}
+
+#[actix_rt::test]
+async fn rspamd_scores_above_the_tag_threshold_are_tagged() {
+    let app = spawn_app_with_daemon(SpamDaemonKind::Rspamd, spawn_rspamd_stub()).await;
+
+    let response = submit(&app, "TAG-ME").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    let backups = backups(&app);
+    assert!(backups[0].contains("Subject: [SPAM] Shaggy (scooby@mystery.van)\r\n"));
+    assert!(backups[0].contains("X-Spam-Score: 6\r\n"));
+}
+
+#[actix_rt::test]
+async fn rspamd_scores_above_the_reject_threshold_are_rejected() {
+    let app = spawn_app_with_daemon(SpamDaemonKind::Rspamd, spawn_rspamd_stub()).await;
+
+    let response = submit(&app, "REJECT-ME").await;
+
+    assert_eq!(reqwest::StatusCode::BAD_REQUEST, response.status());
+    let errors = response.json::<serde_json::Value>().await.unwrap();
+    assert_eq!("Message was rejected as spam.", errors["spam"]);
+    assert!(backups(&app).is_empty());
+}
+
+#[actix_rt::test]
+async fn spamd_scores_above_the_quarantine_threshold_are_quarantined() {
+    let app = spawn_app_with_daemon(SpamDaemonKind::Spamd, spawn_spamd_stub().await).await;
+
+    let response = submit(&app, "QUARANTINE-ME").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert!(backups(&app)[0].contains("X-Spam-Score: 12\r\n"));
+}
+
+#[actix_rt::test]
+async fn spamd_clean_messages_are_delivered_untagged() {
+    let app = spawn_app_with_daemon(SpamDaemonKind::Spamd, spawn_spamd_stub().await).await;
+
+    let response = submit(&app, "Zoinks!").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert!(!backups(&app)[0].contains("X-Spam-Score"));
+}
+
+#[actix_rt::test]
+async fn unreachable_daemon_still_delivers() {
+    let app = spawn_app_with_daemon(SpamDaemonKind::Rspamd, 9).await;
+
+    let response = submit(&app, "REJECT-ME").await;
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+    assert!(!backups(&app)[0].contains("X-Spam-Score"));
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::spawn_app_with;
+use sqlx::sqlite::SqlitePool;
+
+#[actix_rt::test]
+async fn submissions_are_persisted() {
+    let path = std::env::temp_dir().join(format!("submissions-{}.db", uuid::Uuid::new_v4()));
+    let url = format!("sqlite://{}?mode=rwc", path.to_string_lossy());
+
+    let app = spawn_app_with(|settings| {
+        if let Some(storage) = settings.storage.as_mut() {
+            storage.url = url.clone();
+        }
+    })
+    .await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/forms/support", &app.address))
+        .form(&[
+            ("name", "Daphne"),
+            ("email", "daphne@mystery.van"),
+            ("message", "Jeepers!"),
+            ("order_number", "1969"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert!(response.status().is_success());
+
+    let pool = SqlitePool::connect(&url).await.unwrap();
+    let (email, form_id, status, spam_verdict): (String, Option<String>, String, String) =
+        sqlx::query_as("SELECT email, form_id, status, spam_verdict FROM submissions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+
+    assert_eq!("daphne@mystery.van", email);
+    assert_eq!(Some("support".to_owned()), form_id);
+    assert!(["pending", "sent"].contains(&status.as_str()));
+    assert_eq!("deliver", spam_verdict);
+}
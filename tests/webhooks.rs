@@ -0,0 +1,206 @@
+mod common;
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer};
+use common::spawn_app_with;
+use contact_api::settings::WebhookSettings;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+const SECRET: &str = "scooby-snack";
+
+#[derive(Default)]
+struct Received {
+    failures: AtomicUsize,
+    requests: Mutex<Vec<(String, Vec<u8>)>>,
+    idempotency_keys: Mutex<Vec<String>>,
+}
+
+async fn receive(
+    request: HttpRequest,
+    body: web::Bytes,
+    received: web::Data<Received>,
+) -> HttpResponse {
+    let signature = request
+        .headers()
+        .get("X-Contact-Signature")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let idempotency_key = request
+        .headers()
+        .get("Idempotency-Key")
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    received
+        .idempotency_keys
+        .lock()
+        .unwrap()
+        .push(idempotency_key);
+    received
+        .requests
+        .lock()
+        .unwrap()
+        .push((signature, body.to_vec()));
+
+    let failures = received.failures.load(Ordering::SeqCst);
+    if failures > 0 {
+        received.failures.store(failures - 1, Ordering::SeqCst);
+        HttpResponse::InternalServerError().finish()
+    } else {
+        HttpResponse::NoContent().finish()
+    }
+}
+
+/// A local endpoint failing its first `failures` requests.
+fn spawn_receiver(failures: usize) -> (String, web::Data<Received>) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let received = web::Data::new(Received {
+        failures: AtomicUsize::new(failures),
+        ..Received::default()
+    });
+
+    let data = received.clone();
+    let server = HttpServer::new(move || {
+        App::new()
+            .app_data(data.clone())
+            .route("/hook", web::post().to(receive))
+    })
+    .listen(listener)
+    .unwrap()
+    .run();
+    tokio::spawn(server);
+
+    (format!("http://127.0.0.1:{}/hook", port), received)
+}
+
+fn webhook(url: &str) -> WebhookSettings {
+    WebhookSettings {
+        url: url.to_owned(),
+        secret: SECRET.to_owned(),
+        timeout_secs: 5,
+        max_attempts: 1,
+        retry_delay_secs: 0,
+        template: None,
+    }
+}
+
+async fn submit(app: &common::TestApp) {
+    let response = reqwest::Client::new()
+        .post(format!("{}/", &app.address))
+        .form(&[
+            ("name", "Velma"),
+            ("email", "velma@mystery.van"),
+            ("message", "Jinkies!"),
+        ])
+        .send()
+        .await
+        .expect("Failed to execute request");
+
+    assert_eq!(reqwest::StatusCode::ACCEPTED, response.status());
+}
+
+async fn wait_for(received: &Received, count: usize) -> Vec<(String, Vec<u8>)> {
+    for _ in 0..50 {
+        let requests = received.requests.lock().unwrap().clone();
+        if requests.len() >= count {
+            return requests;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    panic!("Webhook was not received.");
+}
+
+#[actix_rt::test]
+async fn submissions_are_posted_as_signed_json() {
+    let (url, received) = spawn_receiver(0);
+    let app = spawn_app_with(|settings| settings.webhooks = vec![webhook(&url)]).await;
+
+    submit(&app).await;
+
+    let (signature, body) = wait_for(&received, 1).await.remove(0);
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+    mac.update(&body);
+    assert_eq!(
+        format!("sha256={}", hex::encode(mac.finalize().into_bytes())),
+        signature
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!("Velma", json["name"]);
+    assert_eq!("velma@mystery.van", json["email"]);
+    assert_eq!("Jinkies!", json["message"]);
+}
+
+#[actix_rt::test]
+async fn failed_webhooks_are_retried() {
+    let (url, received) = spawn_receiver(1);
+    let app = spawn_app_with(|settings| {
+        settings.webhooks = vec![WebhookSettings {
+            max_attempts: 2,
+            ..webhook(&url)
+        }]
+    })
+    .await;
+
+    submit(&app).await;
+
+    let requests = wait_for(&received, 2).await;
+    assert_eq!(requests[0], requests[1]);
+}
+
+#[actix_rt::test]
+async fn outbox_retries_only_the_endpoints_that_failed() {
+    let (failing_url, failing) = spawn_receiver(1);
+    let (url, succeeding) = spawn_receiver(0);
+    let app = spawn_app_with(|settings| {
+        settings.outbox.retry_delay_secs = 0;
+        settings.webhooks = vec![webhook(&failing_url), webhook(&url)];
+    })
+    .await;
+
+    submit(&app).await;
+
+    wait_for(&failing, 2).await;
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    assert_eq!(1, succeeding.requests.lock().unwrap().len());
+
+    let keys = failing.idempotency_keys.lock().unwrap().clone();
+    assert!(!keys[0].is_empty());
+    assert_eq!(keys[0], keys[1]);
+    assert_eq!(keys[0], succeeding.idempotency_keys.lock().unwrap()[0]);
+}
+
+#[actix_rt::test]
+async fn templates_reshape_the_payload() {
+    let (url, received) = spawn_receiver(0);
+    let template = std::env::temp_dir().join(format!("webhook-{}.json", uuid::Uuid::new_v4()));
+    std::fs::write(
+        &template,
+        r#"{"lead": {"name": {{ name | json_encode() }}, "email": {{ email | json_encode() }}}}"#,
+    )
+    .unwrap();
+
+    let app = spawn_app_with(|settings| {
+        settings.webhooks = vec![WebhookSettings {
+            template: Some(template.to_string_lossy().into_owned()),
+            ..webhook(&url)
+        }]
+    })
+    .await;
+
+    submit(&app).await;
+
+    let (_, body) = wait_for(&received, 1).await.remove(0);
+    assert_eq!(
+        serde_json::json!({"lead": {"name": "Velma", "email": "velma@mystery.van"}}),
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    );
+}